@@ -11,7 +11,10 @@ use std::task::{Context, Poll};
 use futures_core::{Stream, TryStream};
 use http_body_util::{BodyExt, Empty, Full};
 
+use http::StatusCode;
+
 use crate::BoxError;
+use crate::response::ErrorResponse;
 
 /// 请求和响应的主体类型。
 pub struct Body {
@@ -61,6 +64,53 @@ impl Body {
         self.collect().await.map(|col| col.to_bytes())
     }
 
+    /// 收集 Body 中的所有数据并合并为单个 Bytes 缓冲区，数据长度超过 `limit` 时返回 [`LengthLimitError`]。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo_core::body::{Body, LengthLimitError};
+    ///
+    /// # async fn example() {
+    /// let bytes = Body::from("hello").to_bytes_limited(5).await.unwrap();
+    /// assert_eq!(bytes, "hello");
+    ///
+    /// let error = Body::from("hello").to_bytes_limited(4).await.unwrap_err();
+    /// assert!(error.is::<LengthLimitError>());
+    /// # }
+    /// ```
+    pub async fn to_bytes_limited(self, limit: usize) -> Result<Bytes, BoxError> {
+        LimitedBody::new(self, limit)
+            .collect()
+            .await
+            .map(|col| col.to_bytes())
+    }
+
+    /// 限制 Body 的数据长度，读取的数据长度超过 `limit` 时返回 [`LengthLimitError`]。
+    ///
+    /// 如果 Body 已经被限制，则使用新的限制替换原有的限制。
+    pub fn limit(self, limit: usize) -> Self {
+        let body = match self.downcast::<LimitedBody>() {
+            Ok(body) => LimitedBody { limit, ..body },
+            Err(body) => LimitedBody::new(body, limit),
+        };
+        Self::new(body)
+    }
+
+    /// 获取 Body 的数据长度限制，Body 没有被限制时返回 `None`。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo_core::body::Body;
+    ///
+    /// assert_eq!(Body::from("hello").length_limit(), None);
+    /// assert_eq!(Body::from("hello").limit(8).length_limit(), Some(8));
+    /// ```
+    pub fn length_limit(&self) -> Option<usize> {
+        self.downcast_ref::<LimitedBody>().map(|body| body.limit)
+    }
+
     /// 尝试将 Body 转换为 `T`，类型不匹配则返回自身。
     ///
     /// 要求 `T: Unpin + 'static`，适用于可安全解除 Pin 的类型。
@@ -261,6 +311,92 @@ where
     }
 }
 
+struct LimitedBody {
+    inner: Body,
+    limit: usize,
+    read: usize,
+}
+
+impl LimitedBody {
+    fn new(inner: Body, limit: usize) -> Self {
+        Self {
+            inner,
+            limit,
+            read: 0,
+        }
+    }
+}
+
+impl HttpBody for LimitedBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let remaining = this.limit.saturating_sub(this.read) as u64;
+        if HttpBody::size_hint(&this.inner).lower() > remaining {
+            return Poll::Ready(Some(Err(LengthLimitError::new(this.limit).into())));
+        }
+        match futures_core::ready!(HttpBody::poll_frame(Pin::new(&mut this.inner), cx)) {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    this.read += data.len();
+                    if this.read > this.limit {
+                        return Poll::Ready(Some(Err(LengthLimitError::new(this.limit).into())));
+                    }
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            frame => Poll::Ready(frame),
+        }
+    }
+
+    #[inline]
+    fn is_end_stream(&self) -> bool {
+        HttpBody::is_end_stream(&self.inner)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> SizeHint {
+        HttpBody::size_hint(&self.inner)
+    }
+}
+
+/// 主体的数据长度超过限制。
+#[derive(Debug, Clone, Copy)]
+pub struct LengthLimitError {
+    limit: usize,
+}
+
+impl LengthLimitError {
+    /// 使用给定的长度限制创建 [`LengthLimitError`]。
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+
+    /// 获取主体的长度限制。
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl std::fmt::Display for LengthLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "payload too large (limit: {} bytes)", self.limit)
+    }
+}
+
+impl std::error::Error for LengthLimitError {}
+
+impl ErrorResponse for LengthLimitError {
+    fn status(&self) -> StatusCode {
+        StatusCode::PAYLOAD_TOO_LARGE
+    }
+}
+
 trait AnyHttpBody {
     fn poll_frame(
         self: Pin<&mut Self>,
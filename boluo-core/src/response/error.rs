@@ -0,0 +1,319 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use http::StatusCode;
+
+use crate::response::{IntoResponse, Response};
+
+/// 可以转换为 [`Response`] 的错误。
+///
+/// # 例子
+///
+/// ```
+/// use boluo_core::http::StatusCode;
+/// use boluo_core::response::ErrorResponse;
+///
+/// #[derive(Debug)]
+/// struct UserNotFound;
+///
+/// impl std::fmt::Display for UserNotFound {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "user not found")
+///     }
+/// }
+///
+/// impl std::error::Error for UserNotFound {}
+///
+/// impl ErrorResponse for UserNotFound {
+///     fn status(&self) -> StatusCode {
+///         StatusCode::NOT_FOUND
+///     }
+/// }
+/// ```
+pub trait ErrorResponse: Error {
+    /// 错误对应的状态码。
+    fn status(&self) -> StatusCode;
+
+    /// 将错误转换为 [`Response`]。
+    ///
+    /// 默认返回仅包含 [`ErrorResponse::status`] 状态码的空响应。
+    fn to_response(&self) -> Response {
+        self.status().into_response_always()
+    }
+}
+
+/// 包装其他错误的透明错误，对应的响应由错误源决定。
+///
+/// [`ErrorResponder`] 查找错误时会跳过已注册的透明错误，使用错误源生成响应后，
+/// 再调用 [`TransparentError::map_response`] 修改响应，例如为响应添加标头。
+pub trait TransparentError: Error {
+    /// 修改错误源对应的响应。
+    fn map_response(&self, response: Response) -> Response;
+}
+
+#[derive(Clone, Copy)]
+struct Resolve {
+    response: fn(&(dyn Error + 'static)) -> Option<Response>,
+    status: fn(&(dyn Error + 'static)) -> Option<StatusCode>,
+}
+
+/// 在错误链中查找已注册的 [`ErrorResponse`] 类型，并将其转换为 [`Response`]。
+///
+/// 克隆 [`ErrorResponder`] 的开销很小，注册的错误类型在克隆之间共享。
+///
+/// # 例子
+///
+/// ```
+/// use boluo_core::BoxError;
+/// use boluo_core::http::StatusCode;
+/// use boluo_core::response::{ErrorResponder, ErrorResponse};
+///
+/// #[derive(Debug)]
+/// struct UserNotFound;
+///
+/// impl std::fmt::Display for UserNotFound {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "user not found")
+///     }
+/// }
+///
+/// impl std::error::Error for UserNotFound {}
+///
+/// impl ErrorResponse for UserNotFound {
+///     fn status(&self) -> StatusCode {
+///         StatusCode::NOT_FOUND
+///     }
+/// }
+///
+/// let responder = ErrorResponder::new().register::<UserNotFound>();
+///
+/// let error = BoxError::from(UserNotFound);
+/// let response = responder.respond(&*error).unwrap();
+///
+/// assert_eq!(response.status(), StatusCode::NOT_FOUND);
+/// ```
+#[derive(Default, Clone)]
+pub struct ErrorResponder {
+    resolvers: Arc<Vec<Resolve>>,
+    transparents: Arc<Vec<Transparent>>,
+}
+
+#[derive(Clone, Copy)]
+struct Transparent {
+    is: fn(&(dyn Error + 'static)) -> bool,
+    map_response: fn(&(dyn Error + 'static), Response) -> Response,
+}
+
+impl ErrorResponder {
+    /// 创建一个没有注册任何错误类型的 [`ErrorResponder`]。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 注册错误类型 `T`。
+    pub fn register<T>(mut self) -> Self
+    where
+        T: ErrorResponse + 'static,
+    {
+        Arc::make_mut(&mut self.resolvers).push(Resolve {
+            response: |error| error.downcast_ref::<T>().map(T::to_response),
+            status: |error| error.downcast_ref::<T>().map(T::status),
+        });
+        self
+    }
+
+    /// 注册透明错误类型 `T`，详情查看 [`TransparentError`]。
+    pub fn register_transparent<T>(mut self) -> Self
+    where
+        T: TransparentError + 'static,
+    {
+        Arc::make_mut(&mut self.transparents).push(Transparent {
+            is: |error| error.is::<T>(),
+            map_response: |error, response| match error.downcast_ref::<T>() {
+                Some(error) => error.map_response(response),
+                None => response,
+            },
+        });
+        self
+    }
+
+    /// 从外到内遍历错误链，使用第一个已注册的错误类型生成响应。
+    ///
+    /// 如果错误链中没有已注册的错误类型，则返回 `None`。
+    pub fn respond(&self, error: &(dyn Error + 'static)) -> Option<Response> {
+        let (response, wrappers) = self.resolve(error, |resolve| resolve.response);
+        response.map(|response| Self::map_response(&wrappers, response))
+    }
+
+    /// 与 [`ErrorResponder::respond`] 相同，错误链中没有已注册的错误类型时，
+    /// 使用 `500 INTERNAL_SERVER_ERROR` 响应。
+    pub fn respond_always(&self, error: &(dyn Error + 'static)) -> Response {
+        let (response, wrappers) = self.resolve(error, |resolve| resolve.response);
+        let response =
+            response.unwrap_or_else(|| StatusCode::INTERNAL_SERVER_ERROR.into_response_always());
+        Self::map_response(&wrappers, response)
+    }
+
+    /// 按照与 [`ErrorResponder::respond`] 相同的规则查找错误，返回对应的状态码，不生成响应。
+    ///
+    /// 如果错误链中没有已注册的错误类型，则返回 `None`。
+    pub fn status(&self, error: &(dyn Error + 'static)) -> Option<StatusCode> {
+        self.resolve(error, |resolve| resolve.status).0
+    }
+
+    /// 查找错误，同时返回途经的透明错误。
+    fn resolve<'a, T>(
+        &self,
+        error: &'a (dyn Error + 'static),
+        f: impl Fn(&Resolve) -> fn(&(dyn Error + 'static)) -> Option<T>,
+    ) -> (Option<T>, Vec<(Transparent, &'a (dyn Error + 'static))>) {
+        let mut wrappers = Vec::new();
+        let mut next = Some(error);
+        while let Some(error) = next {
+            if let Some(transparent) = self.transparents.iter().find(|t| (t.is)(error)) {
+                wrappers.push((*transparent, error));
+            } else if let Some(value) = self.resolvers.iter().find_map(|resolve| f(resolve)(error))
+            {
+                return (Some(value), wrappers);
+            }
+            next = error.source();
+        }
+        (None, wrappers)
+    }
+
+    /// 由内到外调用透明错误的 [`TransparentError::map_response`]。
+    fn map_response(
+        wrappers: &[(Transparent, &(dyn Error + 'static))],
+        response: Response,
+    ) -> Response {
+        wrappers
+            .iter()
+            .rev()
+            .fold(response, |response, (transparent, error)| {
+                (transparent.map_response)(*error, response)
+            })
+    }
+}
+
+impl std::fmt::Debug for ErrorResponder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorResponder")
+            .field("resolvers", &self.resolvers.len())
+            .field("transparents", &self.transparents.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use http::StatusCode;
+
+    use super::{ErrorResponder, ErrorResponse, TransparentError};
+    use crate::response::Response;
+
+    #[derive(Debug)]
+    struct Inner;
+
+    impl std::fmt::Display for Inner {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("inner")
+        }
+    }
+
+    impl Error for Inner {}
+
+    impl ErrorResponse for Inner {
+        fn status(&self) -> StatusCode {
+            StatusCode::BAD_REQUEST
+        }
+    }
+
+    #[derive(Debug)]
+    struct Outer(Inner);
+
+    impl std::fmt::Display for Outer {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("outer")
+        }
+    }
+
+    impl Error for Outer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    impl ErrorResponse for Outer {
+        fn status(&self) -> StatusCode {
+            StatusCode::CONFLICT
+        }
+    }
+
+    #[test]
+    fn respond_walks_source_chain() {
+        let responder = ErrorResponder::new().register::<Inner>();
+        let response = responder.respond(&Outer(Inner)).unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn respond_prefers_outermost() {
+        let responder = ErrorResponder::new()
+            .register::<Inner>()
+            .register::<Outer>();
+        let response = responder.respond(&Outer(Inner)).unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(responder.status(&Outer(Inner)), Some(StatusCode::CONFLICT));
+    }
+
+    #[test]
+    fn respond_unregistered() {
+        let responder = ErrorResponder::new();
+        assert!(responder.respond(&Outer(Inner)).is_none());
+        assert!(responder.status(&Outer(Inner)).is_none());
+    }
+
+    #[derive(Debug)]
+    struct Wrapper(Outer);
+
+    impl std::fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("wrapper")
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    impl TransparentError for Wrapper {
+        fn map_response(&self, mut response: Response) -> Response {
+            response
+                .headers_mut()
+                .insert("x-wrapper", "1".parse().unwrap());
+            response
+        }
+    }
+
+    #[test]
+    fn respond_through_transparent() {
+        let responder = ErrorResponder::new()
+            .register::<Outer>()
+            .register_transparent::<Wrapper>();
+        let error = Wrapper(Outer(Inner));
+        let response = responder.respond(&error).unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()["x-wrapper"], "1");
+        assert_eq!(responder.status(&error), Some(StatusCode::CONFLICT));
+
+        let responder = ErrorResponder::new().register_transparent::<Wrapper>();
+        assert!(responder.respond(&error).is_none());
+        let response = responder.respond_always(&error);
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()["x-wrapper"], "1");
+    }
+}
@@ -1,7 +1,9 @@
 //! HTTP 响应。
 
+mod error;
 mod into_response;
 
+pub use error::{ErrorResponder, ErrorResponse, TransparentError};
 pub use into_response::{HeaderResponseError, IntoResponse, IntoResponseParts};
 
 use std::convert::TryFrom;
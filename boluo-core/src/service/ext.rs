@@ -1,6 +1,8 @@
 use crate::middleware::Middleware;
 use crate::util::assert_service;
 
+#[cfg(feature = "tokio")]
+use super::TimeoutService;
 use super::{
     AndThen, ArcService, BoxCloneService, BoxService, MapErr, MapRequest, MapResponse, MapResult,
     OrElse, Service, Then,
@@ -282,6 +284,35 @@ pub trait ServiceExt<Req>: Service<Req> {
         assert_service(MapRequest::new(self, f))
     }
 
+    /// 限制此服务处理请求的时间，超时返回 [`TimeoutError`]。
+    ///
+    /// 超时错误固定对应 `503 SERVICE_UNAVAILABLE` 响应，需要修改状态码或限制响应主体的时间时，
+    /// 请使用 `boluo` 提供的 `Timeout` 中间件。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use boluo_core::handler::handler_fn;
+    /// use boluo_core::service::ServiceExt;
+    ///
+    /// let service = handler_fn(|| async {
+    ///     tokio::time::sleep(Duration::from_secs(60)).await;
+    /// });
+    /// let service = service.timeout(Duration::from_secs(30));
+    /// ```
+    ///
+    /// [`TimeoutError`]: crate::service::TimeoutError
+    #[cfg(feature = "tokio")]
+    fn timeout(self, duration: std::time::Duration) -> TimeoutService<Self>
+    where
+        Self: Sized,
+        Self::Error: Into<crate::BoxError>,
+    {
+        assert_service(TimeoutService::new(self, duration))
+    }
+
     /// 将此服务转换为 [`Service`] 特征对象并装箱。
     ///
     /// 更多详细信息，请参阅 [`BoxService`]。
@@ -10,6 +10,8 @@ mod map_result;
 mod or_else;
 mod service_fn;
 mod then;
+#[cfg(feature = "tokio")]
+mod timeout;
 
 pub use and_then::AndThen;
 pub use boxed::{ArcService, BoxCloneService, BoxService};
@@ -21,6 +23,8 @@ pub use map_result::MapResult;
 pub use or_else::OrElse;
 pub use service_fn::{ServiceFn, service_fn};
 pub use then::Then;
+#[cfg(feature = "tokio")]
+pub use timeout::{TimeoutError, TimeoutService};
 
 use std::sync::Arc;
 
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use http::StatusCode;
+
+use crate::BoxError;
+use crate::response::ErrorResponse;
+
+use super::Service;
+
+/// [`timeout`] 返回的服务。
+///
+/// [`timeout`]: crate::service::ServiceExt::timeout
+#[derive(Clone, Copy)]
+pub struct TimeoutService<S> {
+    service: S,
+    duration: Duration,
+}
+
+impl<S> TimeoutService<S> {
+    /// 创建一个新的 [`TimeoutService`] 服务。
+    pub fn new(service: S, duration: Duration) -> Self {
+        Self { service, duration }
+    }
+}
+
+impl<S, Req> Service<Req> for TimeoutService<S>
+where
+    S: Service<Req>,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    fn call(
+        &self,
+        request: Req,
+    ) -> impl Future<Output = Result<Self::Response, Self::Error>> + Send {
+        let fut = self.service.call(request);
+        async move {
+            match tokio::time::timeout(self.duration, fut).await {
+                Ok(result) => result.map_err(Into::into),
+                Err(_) => Err(TimeoutError::new(self.duration).into()),
+            }
+        }
+    }
+}
+
+impl<S> std::fmt::Debug for TimeoutService<S>
+where
+    S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutService")
+            .field("service", &self.service)
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+/// 服务处理超时。
+///
+/// 默认转换为 `503 Service Unavailable` 响应，可以使用 [`TimeoutError::with_status`] 修改状态码，
+/// 例如 `408 Request Timeout`。
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutError {
+    duration: Duration,
+    status: StatusCode,
+}
+
+impl TimeoutError {
+    /// 使用给定的超时时间创建 [`TimeoutError`]。
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            status: StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// 设置错误对应的状态码。
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// 获取超时时间。
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl std::fmt::Display for TimeoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "service timed out after {:?}", self.duration)
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+impl ErrorResponse for TimeoutError {
+    fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::service::{Service, ServiceExt, service_fn};
+
+    use super::TimeoutError;
+
+    #[tokio::test(start_paused = true)]
+    async fn timeout() {
+        let service = service_fn(|delay: u64| async move {
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            Ok::<_, std::convert::Infallible>(delay)
+        })
+        .timeout(Duration::from_secs(5));
+
+        assert_eq!(service.call(1).await.unwrap(), 1);
+
+        let error = service.call(10).await.unwrap_err();
+        let error = error.downcast_ref::<TimeoutError>().unwrap();
+        assert_eq!(error.duration(), Duration::from_secs(5));
+    }
+}
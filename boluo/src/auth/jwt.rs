@@ -0,0 +1,215 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use boluo_core::BoxError;
+use jsonwebtoken::Validation;
+use serde::de::DeserializeOwned;
+
+pub use jsonwebtoken::{Algorithm, DecodingKey};
+
+use super::{AuthError, AuthErrorKind, Authenticate, BearerAuth, Credentials};
+
+/// 验证 JSON Web Token 的验证器。
+///
+/// 验证器检查令牌的签名和 `exp`、`nbf` 声明，并在设置后检查 `aud` 和 `iss` 声明。验证成功后，
+/// 令牌的声明会被反序列化为 `T` 作为认证主体。支持 HS、RS、PS、ES 和 EdDSA 系列的签名算法。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::auth::{Algorithm, DecodingKey, Jwt, RequireAuth};
+/// use boluo::data::Extension;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, Clone, Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// #[boluo::route("/me", method = "GET")]
+/// async fn me(Extension(claims): Extension<Claims>) -> String {
+///     claims.sub
+/// }
+///
+/// let jwt = Jwt::<Claims>::new(DecodingKey::from_secret(b"secret"), Algorithm::HS256)
+///     .audience(["api"])
+///     .issuer(["https://auth.example.com"]);
+///
+/// let router = Router::new().mount(me).with(RequireAuth::bearer(jwt));
+/// ```
+pub struct Jwt<T> {
+    key: Arc<DecodingKey>,
+    validation: Validation,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Jwt<T> {
+    /// 使用解码密钥和签名算法创建 [`Jwt`]。
+    pub fn new(key: DecodingKey, algorithm: Algorithm) -> Self {
+        let mut validation = Validation::new(algorithm);
+        validation.validate_nbf = true;
+        validation.validate_aud = false;
+        Self {
+            key: Arc::new(key),
+            validation,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 设置允许的签名算法，算法必须属于同一个系列。
+    pub fn algorithms(mut self, algorithms: impl IntoIterator<Item = Algorithm>) -> Self {
+        self.validation.algorithms = algorithms.into_iter().collect();
+        self
+    }
+
+    /// 设置允许的 `aud` 声明，设置后令牌必须携带其中一个受众。
+    pub fn audience<I>(mut self, audience: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        let audience = audience.into_iter().collect::<Vec<_>>();
+        self.validation.set_audience(&audience);
+        self.validation.validate_aud = true;
+        self
+    }
+
+    /// 设置允许的 `iss` 声明，设置后令牌必须携带其中一个签发者。
+    pub fn issuer<I>(mut self, issuer: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        let issuer = issuer.into_iter().collect::<Vec<_>>();
+        self.validation.set_issuer(&issuer);
+        self
+    }
+
+    /// 设置必须存在的声明，默认为 `exp`。
+    pub fn required_claims<I>(mut self, claims: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: ToString,
+    {
+        let claims = claims.into_iter().collect::<Vec<_>>();
+        self.validation.set_required_spec_claims(&claims);
+        self
+    }
+
+    /// 设置检查 `exp` 和 `nbf` 声明时允许的时钟偏差，默认为 60 秒。
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.validation.leeway = leeway.as_secs();
+        self
+    }
+}
+
+impl<T: DeserializeOwned> Jwt<T> {
+    /// 验证令牌并返回令牌的声明。
+    pub fn decode(&self, token: &str) -> Result<T, AuthError> {
+        jsonwebtoken::decode::<T>(token, &self.key, &self.validation)
+            .map(|data| data.claims)
+            .map_err(|e| AuthError::new(AuthErrorKind::Rejected, BearerAuth::SCHEME).with_source(e))
+    }
+}
+
+impl<T> Clone for Jwt<T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            validation: self.validation.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Jwt<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jwt")
+            .field("validation", &self.validation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Authenticate<BearerAuth> for Jwt<T>
+where
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    type Principal = T;
+
+    async fn authenticate(&self, credentials: BearerAuth) -> Result<Option<T>, BoxError> {
+        self.decode(credentials.token())
+            .map(Some)
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{StatusCode, header};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+    use jsonwebtoken::{EncodingKey, Header, encode, get_current_timestamp};
+    use serde::{Deserialize, Serialize};
+
+    use super::{Algorithm, DecodingKey, Jwt};
+    use crate::auth::RequireAuth;
+    use crate::data::Extension;
+    use crate::response::error_responder;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        aud: String,
+        exp: u64,
+    }
+
+    #[tokio::test]
+    async fn validate_claims() {
+        let jwt = Jwt::<Claims>::new(DecodingKey::from_secret(b"secret"), Algorithm::HS256)
+            .audience(["api"]);
+        let service = RequireAuth::bearer(jwt).transform(handler_fn(
+            |Extension(claims): Extension<Claims>| async move { claims.sub },
+        ));
+
+        let token = |aud: &str, exp: u64| {
+            let claims = Claims {
+                sub: "alice".to_owned(),
+                aud: aud.to_owned(),
+                exp,
+            };
+            encode(
+                &Header::new(Algorithm::HS256),
+                &claims,
+                &EncodingKey::from_secret(b"secret"),
+            )
+            .unwrap()
+        };
+        let call = |token: String| {
+            let request = Request::builder()
+                .header(header::AUTHORIZATION, format!("Bearer {token}"))
+                .body(Body::empty())
+                .unwrap();
+            service.call(request)
+        };
+
+        let now = get_current_timestamp();
+        let response = call(token("api", now + 60)).await.unwrap();
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "alice");
+
+        for token in [token("api", now - 120), token("other", now + 60)] {
+            let error = call(token).await.unwrap_err();
+            let response = error_responder().respond(error.as_ref()).unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                response.headers()[header::WWW_AUTHENTICATE],
+                "Bearer error=\"invalid_token\""
+            );
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! 认证相关的提取器和中间件。
+//!
+//! 提取器 [`BasicAuth`] 和 [`BearerAuth`] 从 `Authorization` 标头中读取凭据，中间件 [`RequireAuth`]
+//! 使用用户提供的验证器验证凭据，并将认证主体保存在请求扩展中。认证失败时返回 [`AuthError`]，
+//! 错误对应的响应为携带 `WWW-Authenticate` 标头的 `401 UNAUTHORIZED`。
+
+#[cfg(feature = "jwt")]
+mod jwt;
+mod require;
+
+#[cfg(feature = "jwt")]
+pub use jwt::{Algorithm, DecodingKey, Jwt};
+pub use require::{Authenticate, RequireAuth, RequireAuthService};
+
+use std::sync::Arc;
+
+use boluo_core::BoxError;
+use boluo_core::extract::{FromRequest, OptionalFromRequest};
+use boluo_core::http::StatusCode;
+use boluo_core::http::header::{self, HeaderMap, HeaderValue};
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+use headers::HeaderMapExt;
+use headers::authorization::{Authorization, Basic, Bearer};
+
+/// 未设置领域时，`Basic` 认证质询使用的领域。
+const DEFAULT_REALM: &str = "restricted";
+
+/// 从请求中读取的认证凭据。
+pub trait Credentials: Sized {
+    /// 认证方案的名称，用于生成 `WWW-Authenticate` 标头。
+    const SCHEME: &'static str;
+
+    /// 从请求标头中解码凭据，凭据不存在时返回 `None`。
+    fn decode(headers: &HeaderMap) -> Result<Option<Self>, AuthError>;
+}
+
+/// 获取 `Basic` 认证凭据的提取器。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::auth::BasicAuth;
+///
+/// #[boluo::route("/", method = "GET")]
+/// async fn handler(auth: BasicAuth) -> String {
+///     format!("hello, {}", auth.username())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicAuth {
+    username: String,
+    password: String,
+}
+
+impl BasicAuth {
+    /// 获取用户名。
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    /// 获取密码。
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+}
+
+impl Credentials for BasicAuth {
+    const SCHEME: &'static str = "Basic";
+
+    fn decode(headers: &HeaderMap) -> Result<Option<Self>, AuthError> {
+        let auth = headers
+            .typed_try_get::<Authorization<Basic>>()
+            .map_err(|e| AuthError::new(AuthErrorKind::Invalid, Self::SCHEME).with_source(e))?;
+        Ok(auth.map(|Authorization(basic)| BasicAuth {
+            username: basic.username().to_owned(),
+            password: basic.password().to_owned(),
+        }))
+    }
+}
+
+/// 获取 `Bearer` 认证令牌的提取器。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::auth::BearerAuth;
+///
+/// #[boluo::route("/", method = "GET")]
+/// async fn handler(auth: BearerAuth) -> String {
+///     format!("token: {}", auth.token())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BearerAuth {
+    token: String,
+}
+
+impl BearerAuth {
+    /// 获取令牌。
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+
+impl Credentials for BearerAuth {
+    const SCHEME: &'static str = "Bearer";
+
+    fn decode(headers: &HeaderMap) -> Result<Option<Self>, AuthError> {
+        let auth = headers
+            .typed_try_get::<Authorization<Bearer>>()
+            .map_err(|e| AuthError::new(AuthErrorKind::Invalid, Self::SCHEME).with_source(e))?;
+        Ok(auth.map(|Authorization(bearer)| BearerAuth {
+            token: bearer.token().to_owned(),
+        }))
+    }
+}
+
+macro_rules! credentials_from_request {
+    ($($ty:ty),+) => {
+        $(
+            impl FromRequest for $ty {
+                type Error = AuthError;
+
+                async fn from_request(request: &mut Request) -> Result<Self, Self::Error> {
+                    <$ty as Credentials>::decode(request.headers())?
+                        .ok_or_else(|| AuthError::new(AuthErrorKind::Missing, Self::SCHEME))
+                }
+            }
+
+            impl OptionalFromRequest for $ty {
+                type Error = AuthError;
+
+                async fn from_request(request: &mut Request) -> Result<Option<Self>, Self::Error> {
+                    <$ty as Credentials>::decode(request.headers())
+                }
+            }
+        )+
+    };
+}
+
+credentials_from_request!(BasicAuth, BearerAuth);
+
+/// 认证错误的类别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthErrorKind {
+    /// 请求没有携带凭据。
+    Missing,
+    /// 凭据格式无效或认证方案不匹配。
+    Invalid,
+    /// 凭据被验证器拒绝。
+    Rejected,
+}
+
+/// 认证错误。
+///
+/// 错误对应的响应为 `401 UNAUTHORIZED`，并携带包含认证方案和领域的 `WWW-Authenticate` 标头。
+/// `Bearer` 认证方案的凭据无效或被拒绝时，标头还会按照 RFC 6750 携带 `error` 参数。
+#[derive(Debug)]
+pub struct AuthError {
+    kind: AuthErrorKind,
+    scheme: &'static str,
+    realm: Option<Arc<str>>,
+    source: Option<BoxError>,
+}
+
+impl AuthError {
+    /// 使用错误类别和认证方案创建 [`AuthError`]。
+    pub fn new(kind: AuthErrorKind, scheme: &'static str) -> Self {
+        Self {
+            kind,
+            scheme,
+            realm: None,
+            source: None,
+        }
+    }
+
+    /// 设置错误源。
+    pub fn with_source(mut self, source: impl Into<BoxError>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// 获取错误类别。
+    pub fn kind(&self) -> AuthErrorKind {
+        self.kind
+    }
+
+    /// 获取认证方案。
+    pub fn scheme(&self) -> &'static str {
+        self.scheme
+    }
+
+    /// 获取认证领域。
+    pub fn realm(&self) -> Option<&str> {
+        self.realm.as_deref()
+    }
+
+    fn with_realm(mut self, realm: Option<&Arc<str>>) -> Self {
+        if self.realm.is_none() {
+            self.realm = realm.cloned();
+        }
+        self
+    }
+
+    fn challenge(&self) -> String {
+        let mut params = Vec::new();
+        match (&self.realm, self.scheme) {
+            (Some(realm), _) => params.push(format!("realm=\"{}\"", escape(realm))),
+            (None, "Basic") => params.push(format!("realm=\"{DEFAULT_REALM}\"")),
+            _ => {}
+        }
+        if self.scheme == "Bearer" {
+            match self.kind {
+                AuthErrorKind::Missing => {}
+                AuthErrorKind::Invalid => params.push("error=\"invalid_request\"".to_owned()),
+                AuthErrorKind::Rejected => params.push("error=\"invalid_token\"".to_owned()),
+            }
+        }
+        if params.is_empty() {
+            self.scheme.to_owned()
+        } else {
+            format!("{} {}", self.scheme, params.join(", "))
+        }
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scheme = self.scheme;
+        match self.kind {
+            AuthErrorKind::Missing => write!(f, "missing `{scheme}` credentials")?,
+            AuthErrorKind::Invalid => write!(f, "invalid `{scheme}` credentials")?,
+            AuthErrorKind::Rejected => write!(f, "`{scheme}` credentials rejected")?,
+        }
+        if let Some(source) = &self.source {
+            write!(f, " ({source})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_deref().map(|e| e as _)
+    }
+}
+
+impl ErrorResponse for AuthError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn to_response(&self) -> Response {
+        let mut response = self.status().into_response_always();
+        if let Ok(value) = HeaderValue::try_from(self.challenge()) {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, value);
+        }
+        response
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{StatusCode, header};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+
+    use super::{BasicAuth, RequireAuth};
+    use crate::data::Extension;
+    use crate::response::error_responder;
+
+    #[derive(Debug, Clone)]
+    struct User(String);
+
+    #[tokio::test]
+    async fn require_basic_auth() {
+        let service = RequireAuth::basic(|auth: BasicAuth| async move {
+            Ok::<_, std::convert::Infallible>(
+                (auth.password() == "secret").then(|| User(auth.username().to_owned())),
+            )
+        })
+        .realm("admin")
+        .transform(handler_fn(|Extension(user): Extension<User>| async move {
+            user.0
+        }));
+        // 验证器和凭据类型不需要实现 `Clone` 和 `Debug`。
+        let service = service.clone();
+        assert!(format!("{service:?}").starts_with("RequireAuthService"));
+
+        let call = |authorization: Option<&'static str>| {
+            let mut builder = Request::builder();
+            if let Some(value) = authorization {
+                builder = builder.header(header::AUTHORIZATION, value);
+            }
+            service.call(builder.body(Body::empty()).unwrap())
+        };
+
+        // alice:secret
+        let response = call(Some("Basic YWxpY2U6c2VjcmV0")).await.unwrap();
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "alice");
+
+        // alice:wrong
+        for authorization in [None, Some("Basic YWxpY2U6d3Jvbmc="), Some("Bearer token")] {
+            let error = call(authorization).await.unwrap_err();
+            let response = error_responder().respond(error.as_ref()).unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                response.headers()[header::WWW_AUTHENTICATE],
+                "Basic realm=\"admin\""
+            );
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use boluo_core::BoxError;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response};
+use boluo_core::service::Service;
+
+use super::{AuthError, AuthErrorKind, BasicAuth, BearerAuth, Credentials};
+
+/// 验证凭据的验证器。
+///
+/// 验证成功时返回认证主体，凭据被拒绝时返回 `None`。验证器返回的错误会原样传播，
+/// 如果错误是 [`AuthError`]，则会被转换为 `401 UNAUTHORIZED` 响应。
+///
+/// 此特征已为 `Fn(C) -> impl Future<Output = Result<Option<P>, E>>` 类型的异步函数实现。
+pub trait Authenticate<C>: Send + Sync {
+    /// 认证主体的类型，验证成功后会被保存在请求扩展中。
+    type Principal: Clone + Send + Sync + 'static;
+
+    /// 验证凭据。
+    fn authenticate(
+        &self,
+        credentials: C,
+    ) -> impl Future<Output = Result<Option<Self::Principal>, BoxError>> + Send;
+}
+
+impl<C, F, Fut, P, E> Authenticate<C> for F
+where
+    C: Send,
+    F: Fn(C) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<Option<P>, E>> + Send,
+    P: Clone + Send + Sync + 'static,
+    E: Into<BoxError>,
+{
+    type Principal = P;
+
+    async fn authenticate(&self, credentials: C) -> Result<Option<P>, BoxError> {
+        self(credentials).await.map_err(Into::into)
+    }
+}
+
+/// 要求请求通过认证的中间件。
+///
+/// 中间件从请求中读取凭据 `C`，并使用验证器验证凭据。验证成功后，认证主体会被保存在请求扩展中，
+/// 可以通过提取器 [`Extension`] 获取。请求没有携带凭据、凭据无效或被拒绝时返回 [`AuthError`]。
+///
+/// # 例子
+///
+/// ```
+/// use std::convert::Infallible;
+///
+/// use boluo::auth::{BearerAuth, RequireAuth};
+/// use boluo::data::Extension;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// #[derive(Debug, Clone)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// async fn validate(auth: BearerAuth) -> Result<Option<User>, Infallible> {
+///     Ok((auth.token() == "secret").then(|| User {
+///         name: "alice".to_owned(),
+///     }))
+/// }
+///
+/// #[boluo::route("/me", method = "GET")]
+/// async fn me(Extension(user): Extension<User>) -> String {
+///     user.name
+/// }
+///
+/// let router = Router::new()
+///     .mount(me)
+///     .with(RequireAuth::bearer(validate).realm("api"));
+/// ```
+///
+/// [`Extension`]: crate::data::Extension
+pub struct RequireAuth<C, V> {
+    validator: Arc<V>,
+    realm: Option<Arc<str>>,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<V> RequireAuth<BasicAuth, V>
+where
+    V: Authenticate<BasicAuth>,
+{
+    /// 创建要求 `Basic` 认证的 [`RequireAuth`]。
+    pub fn basic(validator: V) -> Self {
+        Self::new(validator)
+    }
+}
+
+impl<V> RequireAuth<BearerAuth, V>
+where
+    V: Authenticate<BearerAuth>,
+{
+    /// 创建要求 `Bearer` 认证的 [`RequireAuth`]。
+    pub fn bearer(validator: V) -> Self {
+        Self::new(validator)
+    }
+}
+
+impl<C, V> RequireAuth<C, V>
+where
+    C: Credentials,
+    V: Authenticate<C>,
+{
+    /// 创建读取凭据 `C` 的 [`RequireAuth`]。
+    pub fn new(validator: V) -> Self {
+        Self {
+            validator: Arc::new(validator),
+            realm: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 设置 `WWW-Authenticate` 标头中的认证领域。
+    ///
+    /// # 恐慌
+    ///
+    /// 当领域包含可见 ASCII 字符和空格以外的字符时会触发 panic。
+    pub fn realm(mut self, realm: impl Into<String>) -> Self {
+        let realm = realm.into();
+        assert!(
+            realm.bytes().all(|b| b.is_ascii_graphic() || b == b' '),
+            "realm must be a visible ASCII string"
+        );
+        self.realm = Some(realm.into());
+        self
+    }
+}
+
+impl<C, V> Clone for RequireAuth<C, V> {
+    fn clone(&self) -> Self {
+        Self {
+            validator: self.validator.clone(),
+            realm: self.realm.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C, V> std::fmt::Debug for RequireAuth<C, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequireAuth")
+            .field("credentials", &std::any::type_name::<C>())
+            .field("realm", &self.realm)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C, V, S> Middleware<S> for RequireAuth<C, V> {
+    type Service = RequireAuthService<C, V, S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        RequireAuthService {
+            service,
+            require: self,
+        }
+    }
+}
+
+/// 中间件 [`RequireAuth`] 返回的服务。
+pub struct RequireAuthService<C, V, S> {
+    service: S,
+    require: RequireAuth<C, V>,
+}
+
+impl<C, V, S: Clone> Clone for RequireAuthService<C, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            service: self.service.clone(),
+            require: self.require.clone(),
+        }
+    }
+}
+
+impl<C, V, S: std::fmt::Debug> std::fmt::Debug for RequireAuthService<C, V, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequireAuthService")
+            .field("service", &self.service)
+            .field("require", &self.require)
+            .finish()
+    }
+}
+
+impl<C, V, S> RequireAuthService<C, V, S>
+where
+    C: Credentials + Send,
+    V: Authenticate<C>,
+{
+    async fn authenticate(&self, request: &Request) -> Result<V::Principal, BoxError> {
+        let realm = self.require.realm.as_ref();
+        let credentials = C::decode(request.headers())
+            .and_then(|credentials| {
+                credentials.ok_or_else(|| AuthError::new(AuthErrorKind::Missing, C::SCHEME))
+            })
+            .map_err(|e| e.with_realm(realm))?;
+        match self.require.validator.authenticate(credentials).await {
+            Ok(Some(principal)) => Ok(principal),
+            Ok(None) => Err(AuthError::new(AuthErrorKind::Rejected, C::SCHEME)
+                .with_realm(realm)
+                .into()),
+            Err(e) => match e.downcast::<AuthError>() {
+                Ok(e) => Err((*e).with_realm(realm).into()),
+                Err(e) => Err(e),
+            },
+        }
+    }
+}
+
+impl<C, V, S> Service<Request> for RequireAuthService<C, V, S>
+where
+    C: Credentials + Send,
+    V: Authenticate<C>,
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
+        let principal = self.authenticate(&request).await?;
+        request.extensions_mut().insert(principal);
+        self.service
+            .call(request)
+            .await
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,246 @@
+//! 读取请求 Cookie 和设置响应 Cookie 的类型。
+
+#[cfg(feature = "cookie-private")]
+mod private;
+#[cfg(feature = "cookie-signed")]
+mod signed;
+
+#[cfg(feature = "cookie-private")]
+pub use private::PrivateCookieJar;
+#[cfg(feature = "cookie-signed")]
+pub use signed::SignedCookieJar;
+
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+pub use cookie::Key;
+pub use cookie::time;
+pub use cookie::{Cookie, CookieBuilder, Expiration, SameSite};
+
+use std::convert::Infallible;
+
+use boluo_core::extract::FromRequest;
+use boluo_core::http::header::{self, HeaderMap, HeaderValue, InvalidHeaderValue};
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponseParts, ResponseParts};
+
+/// 读取请求 Cookie 和设置响应 Cookie 的提取器。
+///
+/// 作为提取器时，[`CookieJar`] 解析请求的 `Cookie` 标头。作为响应的一部分时，[`CookieJar`]
+/// 为添加和删除的 Cookie 生成 `Set-Cookie` 标头。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::cookie::{Cookie, CookieJar};
+///
+/// #[boluo::route("/login", method = "POST")]
+/// async fn login(jar: CookieJar) -> (CookieJar, &'static str) {
+///     (jar.insert(Cookie::new("user_id", "42")), "ok")
+/// }
+///
+/// #[boluo::route("/me", method = "GET")]
+/// async fn me(jar: CookieJar) -> String {
+///     match jar.get("user_id") {
+///         Some(cookie) => format!("user {}", cookie.value()),
+///         None => "anonymous".to_owned(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CookieJar {
+    jar: cookie::CookieJar,
+}
+
+impl CookieJar {
+    /// 创建空的 [`CookieJar`]。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 从 `Cookie` 标头中解析 Cookie。
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self {
+            jar: parse_cookies(headers),
+        }
+    }
+
+    /// 获取指定名称的 Cookie。
+    pub fn get(&self, name: &str) -> Option<&Cookie<'static>> {
+        self.jar.get(name)
+    }
+
+    /// 添加 Cookie，响应时会生成对应的 `Set-Cookie` 标头。
+    #[must_use]
+    pub fn insert(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.add(cookie);
+        self
+    }
+
+    /// 删除 Cookie，响应时会生成使客户端 Cookie 过期的 `Set-Cookie` 标头。
+    ///
+    /// 如果 Cookie 设置了 `Path` 或 `Domain`，删除时需要提供相同的值。
+    #[must_use]
+    pub fn remove(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.remove(cookie);
+        self
+    }
+
+    /// 返回遍历所有 Cookie 的迭代器。
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie<'static>> {
+        self.jar.iter()
+    }
+}
+
+impl FromRequest for CookieJar {
+    type Error = Infallible;
+
+    async fn from_request(request: &mut Request) -> Result<Self, Self::Error> {
+        Ok(Self::from_headers(request.headers()))
+    }
+}
+
+impl IntoResponseParts for CookieJar {
+    type Error = InvalidHeaderValue;
+
+    fn into_response_parts(self, parts: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        set_cookies(&self.jar, parts)
+    }
+}
+
+pub(crate) fn parse_cookies(headers: &HeaderMap) -> cookie::CookieJar {
+    let mut jar = cookie::CookieJar::new();
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(Cookie::split_parse)
+        .filter_map(Result::ok)
+        .for_each(|cookie| jar.add_original(cookie.into_owned()));
+    jar
+}
+
+pub(crate) fn set_cookies(
+    jar: &cookie::CookieJar,
+    mut parts: ResponseParts,
+) -> Result<ResponseParts, InvalidHeaderValue> {
+    for cookie in jar.delta() {
+        let value = HeaderValue::try_from(cookie.to_string())?;
+        parts.headers.append(header::SET_COOKIE, value);
+    }
+    Ok(parts)
+}
+
+/// 定义使用 [`Key`] 的 Cookie 提取器，生成结构体、构造方法、[`FromRequest`] 和 [`IntoResponseParts`] 的实现。
+///
+/// 提取时从请求扩展中获取 [`Key`]，缺少 [`Key`] 时返回 [`ExtensionError`](crate::extract::ExtensionError)。
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+macro_rules! key_cookie_jar {
+    ($(#[$attr:meta])* pub struct $name:ident;) => {
+        $(#[$attr])*
+        #[derive(Clone)]
+        pub struct $name {
+            jar: cookie::CookieJar,
+            key: $crate::cookie::Key,
+        }
+
+        impl $name {
+            #[doc = concat!("使用指定的密钥创建空的 [`", stringify!($name), "`]。")]
+            pub fn new(key: $crate::cookie::Key) -> Self {
+                Self {
+                    jar: Default::default(),
+                    key,
+                }
+            }
+
+            /// 从 `Cookie` 标头中解析 Cookie。
+            pub fn from_headers(
+                headers: &boluo_core::http::header::HeaderMap,
+                key: $crate::cookie::Key,
+            ) -> Self {
+                Self {
+                    jar: $crate::cookie::parse_cookies(headers),
+                    key,
+                }
+            }
+        }
+
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("jar", &self.jar)
+                    .finish_non_exhaustive()
+            }
+        }
+
+        impl boluo_core::extract::FromRequest for $name {
+            type Error = $crate::extract::ExtensionError;
+
+            async fn from_request(
+                request: &mut boluo_core::request::Request,
+            ) -> Result<Self, Self::Error> {
+                let key = request
+                    .extensions()
+                    .get::<$crate::cookie::Key>()
+                    .cloned()
+                    .ok_or_else(|| $crate::extract::ExtensionError::MissingExtension {
+                        name: std::any::type_name::<$crate::cookie::Key>(),
+                    })?;
+                Ok(Self::from_headers(request.headers(), key))
+            }
+        }
+
+        impl boluo_core::response::IntoResponseParts for $name {
+            type Error = boluo_core::http::header::InvalidHeaderValue;
+
+            fn into_response_parts(
+                self,
+                parts: boluo_core::response::ResponseParts,
+            ) -> Result<boluo_core::response::ResponseParts, Self::Error> {
+                $crate::cookie::set_cookies(&self.jar, parts)
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+use key_cookie_jar;
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::header;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+
+    use super::{Cookie, CookieJar};
+
+    #[tokio::test]
+    async fn read_and_set() {
+        let service = handler_fn(|jar: CookieJar| async move {
+            let count = jar
+                .get("count")
+                .and_then(|cookie| cookie.value().parse::<u32>().ok())
+                .unwrap_or_default();
+            let jar = jar
+                .insert(Cookie::new("count", (count + 1).to_string()))
+                .remove(Cookie::from("old"));
+            (jar, count.to_string())
+        });
+
+        let request = Request::builder()
+            .header(header::COOKIE, "count=1; old=x")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        let set_cookie = response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|value| value.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(set_cookie.len(), 2);
+        assert!(set_cookie.contains(&"count=2".to_owned()));
+        assert!(set_cookie.iter().any(|value| value.starts_with("old=;")));
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "1");
+    }
+}
@@ -0,0 +1,96 @@
+use super::Cookie;
+
+super::key_cookie_jar! {
+    /// 使用 AEAD 加密的 Cookie 提取器。
+    ///
+    /// 加密可以防止客户端读取和篡改 Cookie 的值。加密使用的 [`Key`](super::Key) 从请求扩展中获取，
+    /// 缺少 [`Key`](super::Key) 时提取失败。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo::cookie::{Cookie, Key, PrivateCookieJar};
+    /// use boluo::data::Extension;
+    /// use boluo::route::Router;
+    /// use boluo::service::ServiceExt;
+    ///
+    /// #[boluo::route("/login", method = "POST")]
+    /// async fn login(jar: PrivateCookieJar) -> (PrivateCookieJar, &'static str) {
+    ///     (jar.insert(Cookie::new("user_id", "42")), "ok")
+    /// }
+    ///
+    /// #[boluo::route("/me", method = "GET")]
+    /// async fn me(jar: PrivateCookieJar) -> String {
+    ///     match jar.get("user_id") {
+    ///         Some(cookie) => format!("user {}", cookie.value()),
+    ///         None => "anonymous".to_owned(),
+    ///     }
+    /// }
+    ///
+    /// let router = Router::new()
+    ///     .mount(login)
+    ///     .mount(me)
+    ///     .with(Extension(Key::generate()));
+    /// ```
+    pub struct PrivateCookieJar;
+}
+
+impl PrivateCookieJar {
+    /// 获取指定名称的 Cookie 并解密，返回的 Cookie 的值为明文。
+    ///
+    /// Cookie 不存在或解密失败时返回 `None`。
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.private(&self.key).get(name)
+    }
+
+    /// 添加 Cookie 并对其加密，响应时会生成对应的 `Set-Cookie` 标头。
+    #[must_use]
+    pub fn insert(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.private_mut(&self.key).add(cookie);
+        self
+    }
+
+    /// 删除 Cookie，响应时会生成使客户端 Cookie 过期的 `Set-Cookie` 标头。
+    #[must_use]
+    pub fn remove(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.private_mut(&self.key).remove(cookie);
+        self
+    }
+
+    /// 返回遍历所有可以解密的 Cookie 的迭代器。
+    pub fn iter(&self) -> impl Iterator<Item = Cookie<'static>> + '_ {
+        let private = self.jar.private(&self.key);
+        self.jar
+            .iter()
+            .filter_map(move |cookie| private.get(cookie.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::http::header::{self, HeaderMap};
+    use boluo_core::response::IntoResponse;
+
+    use super::PrivateCookieJar;
+    use crate::cookie::{Cookie, Key};
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key = Key::generate();
+        let response = PrivateCookieJar::new(key.clone())
+            .insert(Cookie::new("user_id", "42"))
+            .into_response()
+            .unwrap();
+        let set_cookie = response.headers()[header::SET_COOKIE].clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, set_cookie.clone());
+        let jar = PrivateCookieJar::from_headers(&headers, key);
+        assert_eq!(jar.get("user_id").unwrap().value(), "42");
+
+        assert!(!set_cookie.to_str().unwrap().contains("42"));
+
+        let other = PrivateCookieJar::from_headers(&headers, Key::generate());
+        assert!(other.get("user_id").is_none());
+    }
+}
@@ -0,0 +1,96 @@
+use super::Cookie;
+
+super::key_cookie_jar! {
+    /// 使用 HMAC 签名的 Cookie 提取器。
+    ///
+    /// 签名可以防止客户端篡改 Cookie 的值，但 Cookie 的值对客户端仍然可见。签名使用的 [`Key`](super::Key)
+    /// 从请求扩展中获取，缺少 [`Key`](super::Key) 时提取失败。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo::cookie::{Cookie, Key, SignedCookieJar};
+    /// use boluo::data::Extension;
+    /// use boluo::route::Router;
+    /// use boluo::service::ServiceExt;
+    ///
+    /// #[boluo::route("/login", method = "POST")]
+    /// async fn login(jar: SignedCookieJar) -> (SignedCookieJar, &'static str) {
+    ///     (jar.insert(Cookie::new("user_id", "42")), "ok")
+    /// }
+    ///
+    /// #[boluo::route("/me", method = "GET")]
+    /// async fn me(jar: SignedCookieJar) -> String {
+    ///     match jar.get("user_id") {
+    ///         Some(cookie) => format!("user {}", cookie.value()),
+    ///         None => "anonymous".to_owned(),
+    ///     }
+    /// }
+    ///
+    /// let router = Router::new()
+    ///     .mount(login)
+    ///     .mount(me)
+    ///     .with(Extension(Key::generate()));
+    /// ```
+    pub struct SignedCookieJar;
+}
+
+impl SignedCookieJar {
+    /// 获取指定名称的 Cookie 并验证签名，返回的 Cookie 的值不包含签名。
+    ///
+    /// Cookie 不存在或签名无效时返回 `None`。
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.signed(&self.key).get(name)
+    }
+
+    /// 添加 Cookie 并对其签名，响应时会生成对应的 `Set-Cookie` 标头。
+    #[must_use]
+    pub fn insert(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.signed_mut(&self.key).add(cookie);
+        self
+    }
+
+    /// 删除 Cookie，响应时会生成使客户端 Cookie 过期的 `Set-Cookie` 标头。
+    #[must_use]
+    pub fn remove(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.signed_mut(&self.key).remove(cookie);
+        self
+    }
+
+    /// 返回遍历所有签名有效的 Cookie 的迭代器。
+    pub fn iter(&self) -> impl Iterator<Item = Cookie<'static>> + '_ {
+        let signed = self.jar.signed(&self.key);
+        self.jar
+            .iter()
+            .filter_map(move |cookie| signed.get(cookie.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::http::header::{self, HeaderMap, HeaderValue};
+    use boluo_core::response::IntoResponse;
+
+    use super::SignedCookieJar;
+    use crate::cookie::{Cookie, Key};
+
+    #[test]
+    fn sign_and_verify() {
+        let key = Key::generate();
+        let response = SignedCookieJar::new(key.clone())
+            .insert(Cookie::new("user_id", "42"))
+            .into_response()
+            .unwrap();
+        let set_cookie = response.headers()[header::SET_COOKIE].clone();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, set_cookie.clone());
+        let jar = SignedCookieJar::from_headers(&headers, key.clone());
+        assert_eq!(jar.get("user_id").unwrap().value(), "42");
+
+        let tampered = set_cookie.to_str().unwrap().replace("42", "43");
+        headers.insert(header::COOKIE, HeaderValue::try_from(tampered).unwrap());
+        let jar = SignedCookieJar::from_headers(&headers, key);
+        assert!(jar.get("user_id").is_none());
+    }
+}
@@ -0,0 +1,699 @@
+//! 跨站请求伪造（CSRF）防护。
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use boluo_core::BoxError;
+use boluo_core::body::Body;
+use boluo_core::extract::{FromRequest, OptionalFromRequest};
+use boluo_core::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use boluo_core::http::uri::Authority;
+use boluo_core::http::{Method, StatusCode, Uri};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::{Request, RequestParts};
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+use boluo_core::service::Service;
+
+use crate::cookie::{Cookie, Key, SameSite};
+use crate::extract::ExtensionError;
+
+/// 默认的令牌标头。
+const X_CSRF_TOKEN: HeaderName = HeaderName::from_static("x-csrf-token");
+
+/// `Sec-Fetch-Site` 标头。
+const SEC_FETCH_SITE: HeaderName = HeaderName::from_static("sec-fetch-site");
+
+/// 默认的令牌名称，同时用作表单字段名、Cookie 名称和会话键。
+const DEFAULT_NAME: &str = "csrf_token";
+
+/// 默认的表单主体长度限制。
+const DEFAULT_FORM_LIMIT: usize = 64 * 1024;
+
+/// 令牌的最大长度，超过此长度的令牌会被忽略。
+const MAX_TOKEN_LEN: usize = 64;
+
+/// 防护跨站请求伪造的中间件。
+///
+/// 中间件为每个请求准备一个令牌，以 [`CsrfToken`] 的形式保存在请求扩展中，处理程序可以将其嵌入表单或页面。
+/// 令牌有两种保存方式：
+///
+/// - [`Csrf::double_submit`]：令牌保存在使用 HMAC 签名的 Cookie 中，请求提交的令牌必须与 Cookie
+///   中签名有效的令牌一致。签名只能防止伪造令牌，令牌没有与用户或会话绑定，能够为本站设置 Cookie
+///   的攻击者（例如控制了某个子域名）可以先请求任意页面获取有效的 Cookie 和令牌，再将二者植入用户的浏览器。
+///   此时只有来源检查能够阻止攻击，而请求没有携带来源标头时会跳过来源检查，需要防御这类攻击时应使用
+///   [`Csrf::session`]。
+/// - [`Csrf::session`]：令牌保存在会话中，需要在此中间件之外应用 [`SessionManager`]。
+///
+/// 对于 `GET`、`HEAD`、`OPTIONS` 和 `TRACE` 以外的请求，中间件会进行以下检查，检查失败时返回 [`CsrfError`]：
+///
+/// 1. 检查请求的来源。`Sec-Fetch-Site` 标头为 `same-origin` 或 `none` 时通过检查，否则 `Origin`
+///    标头（不存在时使用 `Referer` 标头）必须与请求的 `Host` 标头（不存在时使用请求 URI 的主机部分，
+///    例如 HTTP/2 的 `:authority` 伪标头）一致，或者是受信任的来源。比较时省略的端口视为协议的默认端口。
+///    请求没有携带这些标头时跳过此项检查。
+/// 2. 检查请求提交的令牌。令牌从 `x-csrf-token` 标头中读取，标头不存在且请求主体为
+///    `application/x-www-form-urlencoded` 格式时，从表单的 `csrf_token` 字段中读取。读取表单时
+///    请求主体会被缓冲，然后重新放回请求中，后续的提取器仍然可以读取完整的主体。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::cookie::Key;
+/// use boluo::csrf::{Csrf, CsrfToken};
+/// use boluo::response::Html;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// #[boluo::route("/form", method = "GET")]
+/// async fn form(token: CsrfToken) -> Html<String> {
+///     Html(format!(
+///         r#"<form method="post" action="/submit">
+///             <input type="hidden" name="csrf_token" value="{token}">
+///             <button type="submit">提交</button>
+///         </form>"#
+///     ))
+/// }
+///
+/// #[boluo::route("/submit", method = "POST")]
+/// async fn submit() -> &'static str {
+///     "ok"
+/// }
+///
+/// let router = Router::new()
+///     .mount(form)
+///     .mount(submit)
+///     .with(Csrf::double_submit(Key::generate()).trusted_origin("https://example.com"));
+/// ```
+///
+/// [`SessionManager`]: crate::session::SessionManager
+#[derive(Debug, Clone)]
+pub struct Csrf {
+    storage: Storage,
+    name: Cow<'static, str>,
+    header: HeaderName,
+    secure: bool,
+    trusted_origins: Arc<[String]>,
+    form_limit: usize,
+}
+
+#[derive(Clone)]
+enum Storage {
+    Cookie(Key),
+    #[cfg(feature = "session")]
+    Session,
+}
+
+impl std::fmt::Debug for Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Storage::Cookie(_) => f.debug_tuple("Cookie").finish_non_exhaustive(),
+            #[cfg(feature = "session")]
+            Storage::Session => f.write_str("Session"),
+        }
+    }
+}
+
+impl Csrf {
+    fn new(storage: Storage) -> Self {
+        Self {
+            storage,
+            name: Cow::Borrowed(DEFAULT_NAME),
+            header: X_CSRF_TOKEN,
+            secure: true,
+            trusted_origins: Arc::new([]),
+            form_limit: DEFAULT_FORM_LIMIT,
+        }
+    }
+
+    /// 创建使用签名双重提交 Cookie 模式的 [`Csrf`]，使用 `key` 对令牌签名。
+    ///
+    /// 令牌保存在名为 `csrf_token` 的 Cookie 中，Cookie 设置了 `Path=/`、`Secure` 和 `SameSite=Strict`
+    /// 属性。Cookie 没有设置 `HttpOnly` 属性，前端脚本可以通过 [`CsrfToken`] 嵌入页面的令牌提交，
+    /// Cookie 的值包含签名，不能直接作为令牌提交。
+    pub fn double_submit(key: Key) -> Self {
+        Self::new(Storage::Cookie(key))
+    }
+
+    /// 创建将令牌保存在会话中的 [`Csrf`]。
+    ///
+    /// 令牌保存在会话的 `csrf_token` 键中，需要在此中间件之外应用 [`SessionManager`]。
+    ///
+    /// [`SessionManager`]: crate::session::SessionManager
+    #[cfg(feature = "session")]
+    pub fn session() -> Self {
+        Self::new(Storage::Session)
+    }
+
+    /// 设置令牌的名称，用作表单字段名、Cookie 名称和会话键，默认为 `csrf_token`。
+    pub fn name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// 设置读取令牌的标头，默认为 `x-csrf-token`。
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// 设置保存令牌的 Cookie 是否只能通过 HTTPS 发送，仅对双重提交 Cookie 模式有效。
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// 添加受信任的来源，例如 `https://example.com`。
+    pub fn trusted_origin(mut self, origin: impl Into<String>) -> Self {
+        let mut origins = self.trusted_origins.to_vec();
+        origins.push(origin.into().trim_end_matches('/').to_owned());
+        self.trusted_origins = origins.into();
+        self
+    }
+
+    /// 设置读取表单字段时允许缓冲的最大主体长度，默认为 64 KiB。
+    pub fn form_limit(mut self, limit: usize) -> Self {
+        self.form_limit = limit;
+        self
+    }
+
+    /// 获取请求中已保存的令牌和新生成的令牌，新令牌需要在响应中设置 Cookie。
+    fn token(&self, request: &Request) -> Result<(Option<String>, Option<String>), BoxError> {
+        let current = match &self.storage {
+            Storage::Cookie(key) => crate::cookie::parse_cookies(request.headers())
+                .signed(key)
+                .get(&self.name)
+                .map(|cookie| cookie.value().to_owned()),
+            #[cfg(feature = "session")]
+            Storage::Session => session(request)?.get::<String>(&self.name)?,
+        };
+        if let Some(token) = current.filter(|token| is_valid_token(token)) {
+            return Ok((Some(token), None));
+        }
+
+        let token = generate_token();
+        #[cfg(feature = "session")]
+        if let Storage::Session = self.storage {
+            session(request)?.insert(self.name.as_ref(), &token)?;
+        }
+        Ok((None, Some(token)))
+    }
+
+    fn check_origin(&self, parts: &RequestParts) -> Result<(), CsrfError> {
+        let headers = &parts.headers;
+        if let Some(site) = headers.get(SEC_FETCH_SITE) {
+            if site == "same-origin" || site == "none" {
+                return Ok(());
+            }
+        }
+
+        let origin = match headers.get(header::ORIGIN) {
+            Some(origin) => origin.to_str().ok().map(ToOwned::to_owned),
+            None => match headers.get(header::REFERER) {
+                Some(referer) => referer_origin(referer),
+                None => return Ok(()),
+            },
+        };
+        let Some(origin) = origin.filter(|origin| origin != "null") else {
+            return Err(CsrfError::UntrustedOrigin);
+        };
+
+        if self.trusted_origins.contains(&origin) {
+            return Ok(());
+        }
+        let host = match headers.get(header::HOST) {
+            Some(host) => host.to_str().ok(),
+            None => parts.uri.authority().map(|authority| authority.as_str()),
+        };
+        match host {
+            Some(host) if is_same_origin(&origin, host) => Ok(()),
+            _ => Err(CsrfError::UntrustedOrigin),
+        }
+    }
+
+    /// 读取请求提交的令牌，必要时缓冲表单主体并放回请求中。
+    async fn submitted_token(&self, request: &mut Request) -> Result<Option<String>, BoxError> {
+        if let Some(value) = request.headers().get(&self.header) {
+            return Ok(value.to_str().ok().map(ToOwned::to_owned));
+        }
+        if !is_form(request.headers()) {
+            return Ok(None);
+        }
+
+        let bytes = std::mem::take(request.body_mut())
+            .to_bytes_limited(self.form_limit)
+            .await?;
+        let token = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&bytes)
+            .ok()
+            .and_then(|fields| {
+                fields
+                    .into_iter()
+                    .find(|(name, _)| *name == self.name)
+                    .map(|(_, value)| value)
+            });
+        *request.body_mut() = Body::from(bytes);
+        Ok(token)
+    }
+
+    /// 创建保存令牌的 Cookie，Cookie 的值使用 `key` 签名。
+    fn cookie(&self, key: &Key, token: String) -> Option<Cookie<'static>> {
+        let cookie = Cookie::build((self.name.clone(), token))
+            .path("/")
+            .secure(self.secure)
+            .same_site(SameSite::Strict);
+        let mut jar = cookie::CookieJar::new();
+        jar.signed_mut(key).add(cookie);
+        jar.get(&self.name).cloned()
+    }
+}
+
+impl<S> Middleware<S> for Csrf {
+    type Service = CsrfService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        CsrfService {
+            service,
+            csrf: self,
+        }
+    }
+}
+
+/// 中间件 [`Csrf`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct CsrfService<S> {
+    service: S,
+    csrf: Csrf,
+}
+
+impl<S> Service<Request> for CsrfService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
+        let (current, generated) = self.csrf.token(&request)?;
+
+        if !is_safe_method(request.method()) {
+            self.csrf.check_origin(request.parts())?;
+            let expected = current.as_deref().ok_or(CsrfError::MissingToken)?;
+            let submitted = self
+                .csrf
+                .submitted_token(&mut request)
+                .await?
+                .ok_or(CsrfError::MissingToken)?;
+            if !constant_time_eq(expected.as_bytes(), submitted.as_bytes()) {
+                return Err(CsrfError::InvalidToken.into());
+            }
+        }
+
+        let token = current.or_else(|| generated.clone()).unwrap_or_default();
+        request.extensions_mut().insert(CsrfToken(token));
+
+        let mut response = self
+            .service
+            .call(request)
+            .await
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)?;
+
+        if let (Storage::Cookie(key), Some(token)) = (&self.csrf.storage, generated) {
+            if let Some(cookie) = self.csrf.cookie(key, token) {
+                let value = HeaderValue::try_from(cookie.to_string())?;
+                response.headers_mut().append(header::SET_COOKIE, value);
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// CSRF 令牌。
+///
+/// 由中间件 [`Csrf`] 保存在请求扩展中，可以作为提取器使用。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    /// 获取令牌的字符串形式。
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for CsrfToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for CsrfToken {
+    type Error = ExtensionError;
+
+    async fn from_request(request: &mut Request) -> Result<Self, Self::Error> {
+        let opt = Option::<CsrfToken>::from_request(request)
+            .await
+            .map_err(|e| match e {})?;
+        opt.ok_or_else(|| ExtensionError::MissingExtension {
+            name: std::any::type_name::<CsrfToken>(),
+        })
+    }
+}
+
+impl OptionalFromRequest for CsrfToken {
+    type Error = Infallible;
+
+    async fn from_request(request: &mut Request) -> Result<Option<Self>, Self::Error> {
+        Ok(request.extensions().get::<CsrfToken>().cloned())
+    }
+}
+
+/// CSRF 检查错误。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrfError {
+    /// 请求的来源不受信任。
+    UntrustedOrigin,
+    /// 请求没有提交令牌，或者没有可以比较的令牌。
+    MissingToken,
+    /// 请求提交的令牌无效。
+    InvalidToken,
+}
+
+impl std::fmt::Display for CsrfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsrfError::UntrustedOrigin => f.write_str("untrusted request origin"),
+            CsrfError::MissingToken => f.write_str("missing csrf token"),
+            CsrfError::InvalidToken => f.write_str("invalid csrf token"),
+        }
+    }
+}
+
+impl std::error::Error for CsrfError {}
+
+impl ErrorResponse for CsrfError {
+    fn status(&self) -> StatusCode {
+        StatusCode::FORBIDDEN
+    }
+}
+
+#[cfg(feature = "session")]
+fn session(request: &Request) -> Result<&crate::session::Session, ExtensionError> {
+    request
+        .extensions()
+        .get::<crate::session::Session>()
+        .ok_or_else(|| ExtensionError::MissingExtension {
+            name: std::any::type_name::<crate::session::Session>(),
+        })
+}
+
+fn is_safe_method(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn is_form(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(mime::APPLICATION_WWW_FORM_URLENCODED.as_ref()))
+}
+
+fn referer_origin(referer: &HeaderValue) -> Option<String> {
+    let uri = referer.to_str().ok()?.parse::<Uri>().ok()?;
+    Some(format!("{}://{}", uri.scheme_str()?, uri.authority()?))
+}
+
+/// 判断来源与请求的主机是否一致，省略的端口视为来源协议的默认端口。
+fn is_same_origin(origin: &str, host: &str) -> bool {
+    let Ok(origin) = origin.parse::<Uri>() else {
+        return false;
+    };
+    let (Some(scheme), Some(authority)) = (origin.scheme_str(), origin.authority()) else {
+        return false;
+    };
+    let Ok(host) = host.parse::<Authority>() else {
+        return false;
+    };
+    let default_port = match scheme {
+        "http" => Some(80),
+        "https" => Some(443),
+        _ => None,
+    };
+    authority.host().eq_ignore_ascii_case(host.host())
+        && authority.port_u16().or(default_port) == host.port_u16().or(default_port)
+}
+
+fn is_valid_token(token: &str) -> bool {
+    !token.is_empty()
+        && token.len() <= MAX_TOKEN_LEN
+        && token.bytes().all(|b| b.is_ascii_alphanumeric())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{Method, header};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::Response;
+    use boluo_core::service::Service;
+
+    use super::{Csrf, CsrfError, CsrfToken};
+    use crate::cookie::Key;
+    use crate::data::Form;
+
+    fn set_cookie(response: &Response) -> String {
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        cookie.split(';').next().unwrap().to_owned()
+    }
+
+    #[tokio::test]
+    async fn double_submit() {
+        let service = Csrf::double_submit(Key::generate()).transform(handler_fn(
+            |token: CsrfToken, Form(fields): Form<Vec<(String, String)>>| async move {
+                format!("{token} {}", fields.len())
+            },
+        ));
+
+        // 安全的请求方法不检查令牌，并在响应中设置签名的令牌 Cookie。
+        let request = Request::builder()
+            .method(Method::GET)
+            .body(Body::empty())
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        let cookie = set_cookie(&response);
+        let body = response.into_body().to_bytes().await.unwrap();
+        let token = std::str::from_utf8(&body)
+            .unwrap()
+            .strip_suffix(" 0")
+            .unwrap()
+            .to_owned();
+        assert_ne!(cookie, format!("csrf_token={token}"));
+
+        let post = |origin: &str, cookie: &str, body: String| {
+            Request::builder()
+                .method(Method::POST)
+                .header(header::HOST, "example.com")
+                .header(header::ORIGIN, origin)
+                .header(header::COOKIE, cookie)
+                .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        // 从表单字段读取令牌后，处理程序仍然可以读取完整的表单。
+        let response = service
+            .call(post(
+                "https://example.com",
+                &cookie,
+                format!("a=1&csrf_token={token}"),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.into_body().to_bytes().await.unwrap(),
+            format!("{token} 2")
+        );
+
+        let error = service
+            .call(post(
+                "https://example.com",
+                &cookie,
+                "csrf_token=wrong".to_owned(),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::InvalidToken));
+
+        let error = service
+            .call(post(
+                "https://evil.com",
+                &cookie,
+                format!("csrf_token={token}"),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::UntrustedOrigin));
+
+        // 攻击者植入的未签名令牌无效。
+        let error = service
+            .call(post(
+                "https://example.com",
+                "csrf_token=planted",
+                "csrf_token=planted".to_owned(),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::MissingToken));
+    }
+
+    #[tokio::test]
+    async fn check_origin() {
+        let service = Csrf::double_submit(Key::generate()).transform(handler_fn(
+            |token: CsrfToken| async move { token.to_string() },
+        ));
+        let response = service.call(Request::default()).await.unwrap();
+        let cookie = set_cookie(&response);
+        let token = response.into_body().to_bytes().await.unwrap();
+        let token = std::str::from_utf8(&token).unwrap().to_owned();
+
+        let post = |uri: &str, headers: &[(&str, &str)]| {
+            let mut builder = Request::builder()
+                .method(Method::POST)
+                .uri(uri)
+                .header(header::COOKIE, &cookie)
+                .header("x-csrf-token", &token);
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        // 令牌从标头读取，没有 `Origin` 标头时使用 `Referer` 标头。
+        let request = post(
+            "/",
+            &[
+                ("host", "example.com"),
+                ("referer", "https://example.com/form"),
+            ],
+        );
+        assert!(service.call(request).await.is_ok());
+        let request = post(
+            "/",
+            &[
+                ("host", "example.com"),
+                ("referer", "https://evil.com/form"),
+            ],
+        );
+        let error = service.call(request).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::UntrustedOrigin));
+
+        // 浏览器标记为同源的请求不再检查 `Origin` 标头。
+        let request = post(
+            "/",
+            &[
+                ("host", "example.com"),
+                ("origin", "https://evil.com"),
+                ("sec-fetch-site", "same-origin"),
+            ],
+        );
+        assert!(service.call(request).await.is_ok());
+        let request = post(
+            "/",
+            &[
+                ("host", "example.com"),
+                ("origin", "https://evil.com"),
+                ("sec-fetch-site", "cross-site"),
+            ],
+        );
+        let error = service.call(request).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::UntrustedOrigin));
+
+        // 省略的端口视为协议的默认端口。
+        let request = post(
+            "/",
+            &[
+                ("host", "example.com:443"),
+                ("origin", "https://example.com"),
+            ],
+        );
+        assert!(service.call(request).await.is_ok());
+        let request = post(
+            "/",
+            &[("host", "Example.com"), ("origin", "http://example.com:80")],
+        );
+        assert!(service.call(request).await.is_ok());
+        let request = post(
+            "/",
+            &[
+                ("host", "example.com:8443"),
+                ("origin", "https://example.com"),
+            ],
+        );
+        let error = service.call(request).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::UntrustedOrigin));
+
+        // HTTP/2 请求没有 `Host` 标头，使用请求 URI 的主机部分。
+        let request = post(
+            "https://example.com/submit",
+            &[("origin", "https://example.com")],
+        );
+        assert!(service.call(request).await.is_ok());
+        let request = post(
+            "https://example.com/submit",
+            &[("origin", "https://evil.com")],
+        );
+        let error = service.call(request).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::UntrustedOrigin));
+
+        let mut request = post("/", &[]);
+        request.headers_mut().remove("x-csrf-token");
+        let error = service.call(request).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::MissingToken));
+    }
+
+    #[cfg(feature = "session")]
+    #[tokio::test]
+    async fn session_storage() {
+        use crate::session::{MemoryStore, SessionManager};
+
+        let service = SessionManager::new(MemoryStore::new()).transform(Csrf::session().transform(
+            handler_fn(|token: CsrfToken| async move { token.to_string() }),
+        ));
+
+        // 会话模式不设置令牌 Cookie，令牌保存在会话中。
+        let response = service.call(Request::default()).await.unwrap();
+        let cookie = set_cookie(&response);
+        assert!(!cookie.starts_with("csrf_token="));
+        let token = response.into_body().to_bytes().await.unwrap();
+        let token = std::str::from_utf8(&token).unwrap().to_owned();
+
+        let post = |token: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .header(header::COOKIE, &cookie)
+                .header("x-csrf-token", token)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = service.call(post(&token)).await.unwrap();
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), token);
+
+        let error = service.call(post("wrong")).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&CsrfError::InvalidToken));
+    }
+}
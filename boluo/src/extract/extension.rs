@@ -1,7 +1,9 @@
 use std::convert::Infallible;
 
 use boluo_core::extract::{FromRequest, OptionalFromRequest};
+use boluo_core::http::StatusCode;
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 
 pub use crate::data::Extension;
 
@@ -56,3 +58,10 @@ impl std::fmt::Display for ExtensionError {
 }
 
 impl std::error::Error for ExtensionError {}
+
+impl ErrorResponse for ExtensionError {
+    fn status(&self) -> StatusCode {
+        // 缺少扩展说明服务端没有正确配置中间件。
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
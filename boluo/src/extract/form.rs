@@ -1,10 +1,13 @@
 use boluo_core::BoxError;
-use boluo_core::body::Bytes;
+use boluo_core::body::LengthLimitError;
 use boluo_core::extract::FromRequest;
-use boluo_core::http::{HeaderMap, Method, header};
+use boluo_core::http::{HeaderMap, Method, StatusCode, header};
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 use serde::de::DeserializeOwned;
 
+use crate::middleware::take_limited_body;
+
 pub use crate::data::Form;
 
 use super::{Query, QueryError};
@@ -26,9 +29,10 @@ where
                 return Err(FormError::UnsupportedContentType);
             }
 
-            let bytes = Bytes::from_request(request)
+            let bytes = take_limited_body(request)
+                .to_bytes()
                 .await
-                .map_err(FormError::FailedToBufferBody)?;
+                .map_err(FormError::from_buffer_error)?;
 
             serde_urlencoded::from_bytes::<T>(&bytes)
                 .map(|value| Form(value))
@@ -53,6 +57,8 @@ fn has_content_type(headers: &HeaderMap, expected_content_type: &mime::Mime) ->
 pub enum FormError {
     /// 不支持的内容类型。
     UnsupportedContentType,
+    /// 主体长度超过限制。
+    PayloadTooLarge(LengthLimitError),
     /// 缓冲主体失败。
     FailedToBufferBody(BoxError),
     /// 反序列化失败。
@@ -60,6 +66,13 @@ pub enum FormError {
 }
 
 impl FormError {
+    fn from_buffer_error(error: BoxError) -> Self {
+        match error.downcast::<LengthLimitError>() {
+            Ok(e) => FormError::PayloadTooLarge(*e),
+            Err(e) => FormError::FailedToBufferBody(e),
+        }
+    }
+
     fn from_extract_query_error(error: QueryError) -> Self {
         match error {
             QueryError::FailedToDeserialize(e) => FormError::FailedToDeserialize(e),
@@ -71,6 +84,7 @@ impl std::fmt::Display for FormError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FormError::UnsupportedContentType => f.write_str("unsupported content type"),
+            FormError::PayloadTooLarge(e) => write!(f, "{e}"),
             FormError::FailedToBufferBody(e) => write!(f, "failed to buffer body ({e})"),
             FormError::FailedToDeserialize(e) => {
                 write!(f, "failed to deserialize form ({e})")
@@ -80,3 +94,14 @@ impl std::fmt::Display for FormError {
 }
 
 impl std::error::Error for FormError {}
+
+impl ErrorResponse for FormError {
+    fn status(&self) -> StatusCode {
+        match self {
+            FormError::UnsupportedContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            FormError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            FormError::FailedToBufferBody(_) => StatusCode::BAD_REQUEST,
+            FormError::FailedToDeserialize(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use std::ops::{Deref, DerefMut};
 
 use boluo_core::extract::{FromRequest, OptionalFromRequest};
-use boluo_core::http::HeaderName;
+use boluo_core::http::{HeaderName, StatusCode};
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 use headers::{Header, HeaderMapExt};
 
 /// 获取请求标头值的提取器。
@@ -109,3 +110,9 @@ impl std::fmt::Display for TypedHeaderError {
 }
 
 impl std::error::Error for TypedHeaderError {}
+
+impl ErrorResponse for TypedHeaderError {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
@@ -1,10 +1,13 @@
 use boluo_core::BoxError;
-use boluo_core::body::Bytes;
+use boluo_core::body::LengthLimitError;
 use boluo_core::extract::FromRequest;
-use boluo_core::http::{HeaderMap, header};
+use boluo_core::http::{HeaderMap, StatusCode, header};
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 use serde::de::DeserializeOwned;
 
+use crate::middleware::take_limited_body;
+
 pub use crate::data::Json;
 
 impl<T> FromRequest for Json<T>
@@ -18,9 +21,10 @@ where
             return Err(JsonError::UnsupportedContentType);
         }
 
-        let bytes = Bytes::from_request(request)
+        let bytes = take_limited_body(request)
+            .to_bytes()
             .await
-            .map_err(JsonError::FailedToBufferBody)?;
+            .map_err(JsonError::from_buffer_error)?;
 
         serde_json::from_slice::<T>(&bytes)
             .map(|value| Json(value))
@@ -49,16 +53,28 @@ fn is_json_content_type(headers: &HeaderMap) -> bool {
 pub enum JsonError {
     /// 不支持的内容类型。
     UnsupportedContentType,
+    /// 主体长度超过限制。
+    PayloadTooLarge(LengthLimitError),
     /// 缓冲主体失败。
     FailedToBufferBody(BoxError),
     /// 反序列化失败。
     FailedToDeserialize(serde_json::Error),
 }
 
+impl JsonError {
+    fn from_buffer_error(error: BoxError) -> Self {
+        match error.downcast::<LengthLimitError>() {
+            Ok(e) => JsonError::PayloadTooLarge(*e),
+            Err(e) => JsonError::FailedToBufferBody(e),
+        }
+    }
+}
+
 impl std::fmt::Display for JsonError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             JsonError::UnsupportedContentType => f.write_str("unsupported content type"),
+            JsonError::PayloadTooLarge(e) => write!(f, "{e}"),
             JsonError::FailedToBufferBody(e) => write!(f, "failed to buffer body ({e})"),
             JsonError::FailedToDeserialize(e) => {
                 write!(f, "failed to deserialize json ({e})")
@@ -68,3 +84,17 @@ impl std::fmt::Display for JsonError {
 }
 
 impl std::error::Error for JsonError {}
+
+impl ErrorResponse for JsonError {
+    fn status(&self) -> StatusCode {
+        match self {
+            JsonError::UnsupportedContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            JsonError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            JsonError::FailedToBufferBody(_) => StatusCode::BAD_REQUEST,
+            JsonError::FailedToDeserialize(e) => match e.classify() {
+                serde_json::error::Category::Data => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::BAD_REQUEST,
+            },
+        }
+    }
+}
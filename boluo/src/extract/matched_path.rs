@@ -0,0 +1,62 @@
+use std::convert::Infallible;
+
+use boluo_core::extract::{FromRequest, OptionalFromRequest};
+use boluo_core::request::Request;
+
+use super::ExtensionError;
+
+pub use crate::route::MatchedPath;
+
+impl FromRequest for MatchedPath {
+    type Error = ExtensionError;
+
+    async fn from_request(request: &mut Request) -> Result<Self, Self::Error> {
+        let opt = Option::<MatchedPath>::from_request(request)
+            .await
+            .map_err(|e| match e {})?;
+        opt.ok_or_else(|| ExtensionError::MissingExtension {
+            name: std::any::type_name::<MatchedPath>(),
+        })
+    }
+}
+
+impl OptionalFromRequest for MatchedPath {
+    type Error = Infallible;
+
+    async fn from_request(request: &mut Request) -> Result<Option<Self>, Self::Error> {
+        Ok(request.extensions().get::<MatchedPath>().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+
+    use super::MatchedPath;
+    use crate::route::Router;
+
+    #[tokio::test]
+    async fn extract() {
+        let users = Router::new().route(
+            "/{id}",
+            handler_fn(|path: MatchedPath| async move { path.to_string() }),
+        );
+        let router = Router::new().scope("/users", users);
+
+        let request = Request::builder()
+            .uri("/users/42")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(
+            response.into_body().to_bytes().await.unwrap(),
+            "/users/{id}"
+        );
+
+        let service = handler_fn(|path: MatchedPath| async move { path.to_string() });
+        assert!(service.call(Request::default()).await.is_err());
+    }
+}
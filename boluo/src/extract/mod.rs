@@ -6,6 +6,7 @@ mod extension;
 mod form;
 mod header;
 mod json;
+mod matched_path;
 mod path;
 mod query;
 
@@ -13,5 +14,6 @@ pub use extension::{Extension, ExtensionError};
 pub use form::{Form, FormError};
 pub use header::{TypedHeader, TypedHeaderError};
 pub use json::{Json, JsonError};
+pub use matched_path::MatchedPath;
 pub use path::{Path, PathError, RawPathParams};
 pub use query::{Query, QueryError, RawQuery};
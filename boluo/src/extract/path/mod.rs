@@ -4,7 +4,9 @@ use std::convert::Infallible;
 use std::ops::{Deref, DerefMut};
 
 use boluo_core::extract::FromRequest;
+use boluo_core::http::StatusCode;
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 use serde::de::DeserializeOwned;
 
 use crate::route::PathParams;
@@ -201,6 +203,18 @@ impl std::fmt::Display for PathError {
 
 impl std::error::Error for PathError {}
 
+impl ErrorResponse for PathError {
+    fn status(&self) -> StatusCode {
+        match self {
+            // 参数数量或类型与路由定义不符，属于服务端错误。
+            PathError::WrongNumberOfParameters { .. }
+            | PathError::UnsupportedKeyType { .. }
+            | PathError::UnsupportedValueType { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            PathError::ParseError(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 impl From<de::PathDeError> for PathError {
     fn from(error: de::PathDeError) -> Self {
         match error {
@@ -2,7 +2,9 @@ use std::convert::Infallible;
 use std::ops::{Deref, DerefMut};
 
 use boluo_core::extract::FromRequest;
+use boluo_core::http::StatusCode;
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 use serde::de::DeserializeOwned;
 
 /// 将查询字符串反序列化为某种类型的提取器。
@@ -133,3 +135,11 @@ impl std::fmt::Display for QueryError {
 }
 
 impl std::error::Error for QueryError {}
+
+impl ErrorResponse for QueryError {
+    fn status(&self) -> StatusCode {
+        match self {
+            QueryError::FailedToDeserialize(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
@@ -183,7 +183,9 @@
 //!
 //! 错误和响应是分离的，可以在中间件对特定错误进行捕获，并将错误转换为自己想要的响应格式。
 //!
-//! 未经处理的错误到达服务器时，服务器将返回带有错误信息的 `500 INTERNAL_SERVER_ERROR` 响应。
+//! 框架内置的错误类型（如 [`RouteError`]、[`JsonError`] 等）实现了 [`ErrorResponse`] 特征，
+//! 服务器会在错误链中查找这些类型，并返回对应状态码的响应。其余未经处理的错误到达服务器时，
+//! 服务器将返回 `500 INTERNAL_SERVER_ERROR` 响应。
 //!
 //! ```
 //! use boluo::http::StatusCode;
@@ -255,6 +257,9 @@
 //! [`Router`]: crate::route::Router
 //! [`Middleware`]: crate::middleware::Middleware
 //! [`ServiceExt::with`]: crate::service::ServiceExt::with
+//! [`RouteError`]: crate::route::RouteError
+//! [`JsonError`]: crate::extract::JsonError
+//! [`ErrorResponse`]: crate::response::ErrorResponse
 
 #![forbid(unsafe_code)]
 #![warn(
@@ -270,6 +275,7 @@ pub use boluo_core::{body, handler, http, request, service, upgrade};
 
 pub use boluo_macros::route;
 
+pub mod auth;
 pub mod data;
 pub mod extract;
 pub mod listener;
@@ -290,3 +296,18 @@ pub mod ws;
 
 #[cfg(feature = "static-file")]
 pub mod static_file;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "cookie")]
+pub mod cookie;
+
+#[cfg(feature = "session")]
+pub mod session;
+
+#[cfg(feature = "csrf")]
+pub mod csrf;
+
+#[cfg(feature = "test-util")]
+pub mod test;
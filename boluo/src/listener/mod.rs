@@ -1,7 +1,19 @@
 //! 监听器的特征和相关类型的定义。
 
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(all(unix, feature = "tokio"))]
+mod unix;
+
+#[cfg(feature = "tls")]
+pub use tls::{CertResolver, TlsConfig, TlsConnectInfo, TlsError, TlsListener};
+#[cfg(all(unix, feature = "tokio"))]
+pub use unix::{UnixConnectInfo, UnixSocketListener};
+
 use std::net::SocketAddr;
 
+use boluo_core::http::Extensions;
+
 /// 连接信息。
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ConnectInfo {
@@ -26,6 +38,17 @@ pub trait Listener {
     fn accept(
         &mut self,
     ) -> impl Future<Output = Result<(Self::IO, Self::Addr), Self::Error>> + Send;
+
+    /// 将连接地址添加到请求的扩展中。
+    ///
+    /// 默认只添加连接地址本身。包装其他监听器的监听器应同时添加内部监听器的连接地址，
+    /// 使依赖 [`ConnectInfo`] 等地址类型的中间件在包装后仍然可用。
+    fn insert_addr(addr: &Self::Addr, extensions: &mut Extensions)
+    where
+        Self::Addr: Clone + Send + Sync + 'static,
+    {
+        extensions.insert(addr.clone());
+    }
 }
 
 #[cfg(feature = "tokio")]
@@ -0,0 +1,605 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::{Duration, SystemTime};
+
+use boluo_core::http::Extensions;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::CryptoProvider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert, danger::ClientCertVerifier};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::{self, ServerConfig};
+use tokio_rustls::server::TlsStream;
+
+use super::Listener;
+
+type Handshake<IO, A> = Option<(TlsStream<IO>, TlsConnectInfo<A>)>;
+
+/// 使用 TLS 加密连接的监听器。
+///
+/// [`TlsListener`] 包装任意监听器，在后台任务中完成 TLS 握手，握手缓慢的连接不会阻塞接收新连接。
+/// 握手失败或超时的连接会被直接关闭。正在握手的连接数达到 [`TlsListener::max_pending_handshakes`]
+/// 时，监听器暂停接收新连接，直到有握手完成。
+///
+/// # 例子
+///
+/// ```no_run
+/// use boluo::listener::{CertResolver, TlsConfig, TlsListener};
+/// use boluo::route::Router;
+/// use boluo::server::Server;
+/// use tokio::net::TcpListener;
+///
+/// # async fn run() {
+/// let resolver = CertResolver::from_pem_file("cert.pem", "key.pem").await.unwrap();
+/// let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+/// let listener = TlsListener::new(listener, TlsConfig::new(resolver));
+///
+/// Server::new(listener).run(Router::new()).await.unwrap();
+/// # }
+/// ```
+pub struct TlsListener<L: Listener> {
+    listener: L,
+    acceptor: TlsAcceptor,
+    handshake_timeout: Duration,
+    max_pending_handshakes: usize,
+    handshakes: JoinSet<Handshake<L::IO, L::Addr>>,
+}
+
+impl<L: Listener> TlsListener<L> {
+    /// 使用监听器和 TLS 配置创建 [`TlsListener`]。
+    ///
+    /// # 恐慌
+    ///
+    /// 当进程默认的 [`CryptoProvider`] 不支持安全的默认 TLS 协议版本时会触发 panic。
+    pub fn new(listener: L, config: TlsConfig) -> Self {
+        Self::from_server_config(listener, config.server_config())
+    }
+
+    /// 使用监听器和 [`ServerConfig`] 创建 [`TlsListener`]。
+    pub fn from_server_config(listener: L, config: Arc<ServerConfig>) -> Self {
+        Self {
+            listener,
+            acceptor: TlsAcceptor::from(config),
+            handshake_timeout: Duration::from_secs(10),
+            max_pending_handshakes: 1024,
+            handshakes: JoinSet::new(),
+        }
+    }
+
+    /// 设置 TLS 握手的超时时间。
+    ///
+    /// 默认值为 10 秒。
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// 设置同时进行 TLS 握手的连接数上限。
+    ///
+    /// 达到上限后暂停从内部监听器接收新连接，避免大量不发送握手数据的连接占用资源。默认值为 1024。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `max` 为零时会触发 panic。
+    pub fn max_pending_handshakes(mut self, max: usize) -> Self {
+        assert!(max > 0, "pending handshake limit must be positive");
+        self.max_pending_handshakes = max;
+        self
+    }
+
+    /// 获取内部的监听器。
+    pub fn get_ref(&self) -> &L {
+        &self.listener
+    }
+}
+
+impl<L: Listener> std::fmt::Debug for TlsListener<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsListener")
+            .field("listener", &std::any::type_name::<L>())
+            .field("handshake_timeout", &self.handshake_timeout)
+            .field("max_pending_handshakes", &self.max_pending_handshakes)
+            .field("handshakes", &self.handshakes.len())
+            .finish()
+    }
+}
+
+impl<L> Listener for TlsListener<L>
+where
+    L: Listener + Send,
+    L::IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    L::Addr: Clone + Send + Sync + 'static,
+{
+    type IO = TlsStream<L::IO>;
+    type Addr = TlsConnectInfo<L::Addr>;
+    type Error = L::Error;
+
+    async fn accept(&mut self) -> Result<(Self::IO, Self::Addr), Self::Error> {
+        loop {
+            tokio::select! {
+                incoming = self.listener.accept(),
+                    if self.handshakes.len() < self.max_pending_handshakes =>
+                {
+                    let (io, addr) = incoming?;
+                    self.handshakes.spawn(handshake(
+                        self.acceptor.clone(),
+                        io,
+                        addr,
+                        self.handshake_timeout,
+                    ));
+                }
+                Some(result) = self.handshakes.join_next() => {
+                    if let Ok(Some(conn)) = result {
+                        return Ok(conn);
+                    }
+                }
+            }
+        }
+    }
+
+    fn insert_addr(addr: &Self::Addr, extensions: &mut Extensions)
+    where
+        Self::Addr: Clone + Send + Sync + 'static,
+    {
+        extensions.insert(addr.clone());
+        L::insert_addr(addr.addr(), extensions);
+    }
+}
+
+async fn handshake<IO, A>(
+    acceptor: TlsAcceptor,
+    io: IO,
+    addr: A,
+    timeout: Duration,
+) -> Handshake<IO, A>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let stream = match tokio::time::timeout(timeout, acceptor.accept(io)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(_e)) => {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(error = %_e, "tls handshake failed");
+            return None;
+        }
+        Err(_) => {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("tls handshake timed out");
+            return None;
+        }
+    };
+
+    let (_, conn) = stream.get_ref();
+    let info = TlsConnectInfo {
+        addr,
+        server_name: conn.server_name().map(Arc::from),
+        alpn_protocol: conn.alpn_protocol().map(Arc::from),
+        peer_certificates: conn.peer_certificates().map(Arc::from),
+    };
+    Some((stream, info))
+}
+
+/// TLS 连接信息。
+#[derive(Debug, Clone)]
+pub struct TlsConnectInfo<A> {
+    addr: A,
+    server_name: Option<Arc<str>>,
+    alpn_protocol: Option<Arc<[u8]>>,
+    peer_certificates: Option<Arc<[CertificateDer<'static>]>>,
+}
+
+impl<A> TlsConnectInfo<A> {
+    /// 获取内部监听器返回的连接地址。
+    pub fn addr(&self) -> &A {
+        &self.addr
+    }
+
+    /// 获取客户端通过 SNI 请求的服务器名称。
+    pub fn server_name(&self) -> Option<&str> {
+        self.server_name.as_deref()
+    }
+
+    /// 获取通过 ALPN 协商的应用层协议。
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn_protocol.as_deref()
+    }
+
+    /// 获取客户端提供的证书链，未启用客户端认证时返回 `None`。
+    pub fn peer_certificates(&self) -> Option<&[CertificateDer<'static>]> {
+        self.peer_certificates.as_deref()
+    }
+}
+
+/// [`TlsListener`] 的 TLS 配置。
+///
+/// 默认根据启用的功能通过 ALPN 协商 `h2` 和 `http/1.1` 协议。
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    resolver: Arc<dyn ResolvesServerCert>,
+    client_cert_verifier: Option<Arc<dyn ClientCertVerifier>>,
+    alpn_protocols: Vec<Vec<u8>>,
+}
+
+impl TlsConfig {
+    /// 使用证书解析器创建 [`TlsConfig`]，每次握手时由解析器选择证书。
+    pub fn new(resolver: impl ResolvesServerCert + 'static) -> Self {
+        Self {
+            resolver: Arc::new(resolver),
+            client_cert_verifier: None,
+            alpn_protocols: vec![
+                #[cfg(feature = "http2")]
+                b"h2".to_vec(),
+                #[cfg(feature = "http1")]
+                b"http/1.1".to_vec(),
+            ],
+        }
+    }
+
+    /// 使用函数创建 [`TlsConfig`]，每次握手时调用函数选择证书，返回 `None` 时终止握手。
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(ClientHello<'_>) -> Option<Arc<CertifiedKey>> + Send + Sync + 'static,
+    {
+        Self::new(FnResolver(f))
+    }
+
+    /// 设置验证客户端证书的验证器，启用客户端认证。
+    pub fn client_cert_verifier(mut self, verifier: Arc<dyn ClientCertVerifier>) -> Self {
+        self.client_cert_verifier = Some(verifier);
+        self
+    }
+
+    /// 设置通过 ALPN 协商的应用层协议，按优先级排列。
+    pub fn alpn_protocols<I, P>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<Vec<u8>>,
+    {
+        self.alpn_protocols = protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn server_config(self) -> Arc<ServerConfig> {
+        let builder = ServerConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()
+            .expect("crypto provider does not support the default protocol versions");
+        let builder = match self.client_cert_verifier {
+            Some(verifier) => builder.with_client_cert_verifier(verifier),
+            None => builder.with_no_client_auth(),
+        };
+        let mut config = builder.with_cert_resolver(self.resolver);
+        config.alpn_protocols = self.alpn_protocols;
+        Arc::new(config)
+    }
+}
+
+struct FnResolver<F>(F);
+
+impl<F> std::fmt::Debug for FnResolver<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("FnResolver").finish()
+    }
+}
+
+impl<F> ResolvesServerCert for FnResolver<F>
+where
+    F: Fn(ClientHello<'_>) -> Option<Arc<CertifiedKey>> + Send + Sync,
+{
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        (self.0)(client_hello)
+    }
+}
+
+/// 支持热重载的证书解析器。
+///
+/// 所有握手使用同一个证书，调用 [`CertResolver::set`] 或重载方法替换证书后，
+/// 新的握手会立即使用新证书，已建立的连接不受影响。
+#[derive(Debug, Clone)]
+pub struct CertResolver {
+    key: Arc<RwLock<Arc<CertifiedKey>>>,
+}
+
+impl CertResolver {
+    /// 使用证书和私钥创建 [`CertResolver`]。
+    pub fn new(key: Arc<CertifiedKey>) -> Self {
+        Self {
+            key: Arc::new(RwLock::new(key)),
+        }
+    }
+
+    /// 使用 PEM 格式的证书链和私钥创建 [`CertResolver`]，私钥与证书不匹配时返回错误。
+    pub fn from_pem(cert: &[u8], key: &[u8]) -> Result<Self, TlsError> {
+        certified_key(cert, key).map(Self::new)
+    }
+
+    /// 从文件读取 PEM 格式的证书链和私钥，创建 [`CertResolver`]。
+    pub async fn from_pem_file(
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> Result<Self, TlsError> {
+        read_certified_key(cert.as_ref(), key.as_ref())
+            .await
+            .map(Self::new)
+    }
+
+    /// 替换证书和私钥。
+    pub fn set(&self, key: Arc<CertifiedKey>) {
+        *self.key.write().unwrap_or_else(|e| e.into_inner()) = key;
+    }
+
+    /// 使用 PEM 格式的证书链和私钥替换当前证书。
+    pub fn reload_from_pem(&self, cert: &[u8], key: &[u8]) -> Result<(), TlsError> {
+        certified_key(cert, key).map(|key| self.set(key))
+    }
+
+    /// 从文件读取 PEM 格式的证书链和私钥，替换当前证书。
+    pub async fn reload_from_pem_file(
+        &self,
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> Result<(), TlsError> {
+        read_certified_key(cert.as_ref(), key.as_ref())
+            .await
+            .map(|key| self.set(key))
+    }
+
+    /// 启动后台任务，每隔 `interval` 检查证书和私钥文件的修改时间，文件变化后重载证书。
+    ///
+    /// 重载失败（包括只更新了证书或私钥其中一个导致二者不匹配）时保留当前证书，并在下次检查时重试。所有 [`CertResolver`] 被丢弃后任务自动结束。
+    pub fn watch_pem_file(
+        &self,
+        cert: impl Into<PathBuf>,
+        key: impl Into<PathBuf>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let resolver = Arc::downgrade(&self.key);
+        let (cert, key) = (cert.into(), key.into());
+
+        tokio::spawn(async move {
+            let mut last_modified = modified(&cert, &key).await;
+            let mut interval = tokio::time::interval(interval);
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                let Some(resolver) = Weak::upgrade(&resolver).map(|key| CertResolver { key })
+                else {
+                    return;
+                };
+
+                let current = modified(&cert, &key).await;
+                if current.is_none() || current == last_modified {
+                    continue;
+                }
+                match resolver.reload_from_pem_file(&cert, &key).await {
+                    Ok(()) => last_modified = current,
+                    Err(_e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %_e, "failed to reload tls certificate");
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, _: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.key.read().unwrap_or_else(|e| e.into_inner()).clone())
+    }
+}
+
+async fn modified(cert: &Path, key: &Path) -> Option<(SystemTime, SystemTime)> {
+    let cert = tokio::fs::metadata(cert).await.ok()?.modified().ok()?;
+    let key = tokio::fs::metadata(key).await.ok()?.modified().ok()?;
+    Some((cert, key))
+}
+
+async fn read_certified_key(cert: &Path, key: &Path) -> Result<Arc<CertifiedKey>, TlsError> {
+    let cert = tokio::fs::read(cert).await.map_err(TlsError::Io)?;
+    let key = tokio::fs::read(key).await.map_err(TlsError::Io)?;
+    certified_key(&cert, &key)
+}
+
+fn certified_key(cert: &[u8], key: &[u8]) -> Result<Arc<CertifiedKey>, TlsError> {
+    let cert = CertificateDer::pem_slice_iter(cert)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(TlsError::Pem)?;
+    if cert.is_empty() {
+        return Err(TlsError::MissingCertificate);
+    }
+    let key = PrivateKeyDer::from_pem_slice(key).map_err(TlsError::Pem)?;
+    let key = crypto_provider()
+        .key_provider
+        .load_private_key(key)
+        .map_err(TlsError::Rustls)?;
+    let key = CertifiedKey::new(cert, key);
+    // 轮换证书时证书和私钥可能不是同时写入的，不匹配时保留当前证书。
+    match key.keys_match() {
+        Ok(()) | Err(rustls::Error::InconsistentKeys(rustls::InconsistentKeys::Unknown)) => {}
+        Err(e) => return Err(TlsError::Rustls(e)),
+    }
+    Ok(Arc::new(key))
+}
+
+fn crypto_provider() -> Arc<CryptoProvider> {
+    CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::ring::default_provider()))
+}
+
+/// 加载证书时发生的错误。
+#[derive(Debug)]
+pub enum TlsError {
+    /// 读取文件失败。
+    Io(std::io::Error),
+    /// 解析 PEM 数据失败。
+    Pem(rustls::pki_types::pem::Error),
+    /// PEM 数据中没有证书。
+    MissingCertificate,
+    /// 加载私钥失败或私钥与证书不匹配。
+    Rustls(rustls::Error),
+}
+
+impl std::fmt::Display for TlsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TlsError::Io(e) => write!(f, "failed to read certificate file ({e})"),
+            TlsError::Pem(e) => write!(f, "failed to parse pem data ({e})"),
+            TlsError::MissingCertificate => f.write_str("no certificate found in pem data"),
+            TlsError::Rustls(e) => write!(f, "failed to load private key ({e})"),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TlsError::Io(e) => Some(e),
+            TlsError::Pem(e) => Some(e),
+            TlsError::MissingCertificate => None,
+            TlsError::Rustls(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+
+    use super::{CertResolver, TlsConfig, TlsError, TlsListener, crypto_provider};
+    use crate::listener::Listener;
+
+    /// 创建自签名证书的解析器和信任该证书的客户端。
+    fn certificate(alpn_protocols: &[&[u8]]) -> (CertResolver, TlsConnector) {
+        let cert = rcgen::generate_simple_self_signed(["localhost".to_owned()]).unwrap();
+        let resolver = CertResolver::from_pem(
+            cert.cert.pem().as_bytes(),
+            cert.signing_key.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert.cert.der().clone()).unwrap();
+        let mut config = ClientConfig::builder_with_provider(crypto_provider())
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        config.alpn_protocols = alpn_protocols.iter().map(|p| p.to_vec()).collect();
+        (resolver, TlsConnector::from(Arc::new(config)))
+    }
+
+    #[tokio::test]
+    async fn handshake() {
+        let (resolver, connector) = certificate(&[b"h2"]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut listener =
+            TlsListener::new(listener, TlsConfig::new(resolver).alpn_protocols(["h2"]));
+
+        // 未完成握手的连接不会阻塞后续连接。
+        let _idle = TcpStream::connect(addr).await.unwrap();
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            connector.connect(server_name, stream).await.unwrap()
+        });
+
+        let (_, info) = listener.accept().await.unwrap();
+        assert_eq!(info.server_name(), Some("localhost"));
+        assert_eq!(info.alpn_protocol(), Some(&b"h2"[..]));
+        assert!(info.peer_certificates().is_none());
+        client.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn max_pending_handshakes() {
+        let (resolver, connector) = certificate(&[]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let timeout = Duration::from_millis(200);
+        let mut listener = TlsListener::new(listener, TlsConfig::new(resolver))
+            .handshake_timeout(timeout)
+            .max_pending_handshakes(1);
+
+        // 未完成握手的连接占满上限，后续连接需要等待它超时后才会被接收。
+        let _idle = TcpStream::connect(addr).await.unwrap();
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            connector.connect(server_name, stream).await.unwrap()
+        });
+
+        let start = Instant::now();
+        let (_, info) = listener.accept().await.unwrap();
+        assert!(start.elapsed() >= timeout);
+        assert_eq!(info.server_name(), Some("localhost"));
+        client.await.unwrap();
+    }
+
+    #[cfg(feature = "http1")]
+    #[tokio::test]
+    async fn rate_limit() {
+        use boluo_core::handler::handler_fn;
+        use boluo_core::middleware::Middleware;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::middleware::{Quota, RateLimit};
+        use crate::server::Server;
+
+        let (resolver, connector) = certificate(&[b"http/1.1"]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let listener = TlsListener::new(listener, TlsConfig::new(resolver));
+
+        // 默认的限流键使用内部监听器的连接地址。
+        let service = RateLimit::new(Quota::per_minute(1)).transform(handler_fn(|| async { "ok" }));
+        tokio::spawn(async move { Server::new(listener).run(service).await });
+
+        let mut statuses = Vec::new();
+        for _ in 0..2 {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            let mut stream = connector.connect(server_name, stream).await.unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            let mut response = Vec::new();
+            let _ = stream.read_to_end(&mut response).await;
+            statuses.push(String::from_utf8_lossy(&response[..12]).into_owned());
+        }
+        assert_eq!(statuses, ["HTTP/1.1 200", "HTTP/1.1 429"]);
+    }
+
+    #[test]
+    fn mismatched_key() {
+        let first = rcgen::generate_simple_self_signed(["localhost".to_owned()]).unwrap();
+        let second = rcgen::generate_simple_self_signed(["localhost".to_owned()]).unwrap();
+        let cert = first.cert.pem();
+
+        let resolver = CertResolver::from_pem(
+            cert.as_bytes(),
+            first.signing_key.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+        let result = resolver.reload_from_pem(
+            cert.as_bytes(),
+            second.signing_key.serialize_pem().as_bytes(),
+        );
+        assert!(matches!(result, Err(TlsError::Rustls(_))));
+    }
+}
@@ -0,0 +1,215 @@
+use std::io::ErrorKind;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tokio::net::unix::{SocketAddr, UCred};
+use tokio::net::{UnixListener, UnixStream};
+
+use super::Listener;
+
+/// Unix 域套接字的连接信息。
+#[derive(Debug, Clone)]
+pub struct UnixConnectInfo {
+    /// 连接的本地地址。
+    pub local: SocketAddr,
+    /// 连接的远程地址。
+    pub remote: SocketAddr,
+    /// 对端进程的凭据，当前平台不支持时为 `None`。
+    pub peer_cred: Option<UCred>,
+}
+
+impl Listener for UnixListener {
+    type IO = UnixStream;
+    type Addr = UnixConnectInfo;
+    type Error = std::io::Error;
+
+    async fn accept(&mut self) -> std::io::Result<(Self::IO, Self::Addr)> {
+        let (conn, remote) = UnixListener::accept(self).await?;
+        let local = self.local_addr()?;
+        let peer_cred = conn.peer_cred().ok();
+        Ok((
+            conn,
+            UnixConnectInfo {
+                local,
+                remote,
+                peer_cred,
+            },
+        ))
+    }
+}
+
+/// 管理套接字文件的 Unix 域套接字监听器。
+///
+/// 绑定时会删除上次运行遗留的套接字文件，监听器被丢弃时会删除创建的套接字文件。
+/// 套接字文件已经被其他进程替换时不会删除。
+///
+/// # 例子
+///
+/// ```no_run
+/// use boluo::listener::UnixSocketListener;
+/// use boluo::route::Router;
+/// use boluo::server::Server;
+///
+/// # async fn run() {
+/// let listener = UnixSocketListener::bind_with_mode("/run/boluo.sock", 0o660).unwrap();
+///
+/// Server::new(listener).run(Router::new()).await.unwrap();
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct UnixSocketListener {
+    listener: UnixListener,
+    path: PathBuf,
+    file: (u64, u64),
+}
+
+impl UnixSocketListener {
+    /// 在指定路径创建套接字文件并监听。
+    pub fn bind(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        remove_stale_socket(&path)?;
+        let listener = UnixListener::bind(&path)?;
+        Self::new(listener, path)
+    }
+
+    /// 在指定路径创建套接字文件并监听，并将套接字文件的权限设置为 `mode`。
+    ///
+    /// 套接字文件先在同一目录下仅当前用户可以访问的临时目录中创建并设置权限，再链接到指定路径，
+    /// 指定路径上的套接字文件不会以更宽松的权限存在。
+    pub fn bind_with_mode(path: impl Into<PathBuf>, mode: u32) -> std::io::Result<Self> {
+        let path = path.into();
+        remove_stale_socket(&path)?;
+
+        let dir = private_dir(&path)?;
+        let tmp = dir.join("s");
+        let result = UnixListener::bind(&tmp).and_then(|listener| {
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(mode))?;
+            // 硬链接不会覆盖已经存在的文件。
+            std::fs::hard_link(&tmp, &path)?;
+            Ok(listener)
+        });
+        let _ = std::fs::remove_file(&tmp);
+        let _ = std::fs::remove_dir(&dir);
+        Self::new(result?, path)
+    }
+
+    fn new(listener: UnixListener, path: PathBuf) -> std::io::Result<Self> {
+        let metadata = std::fs::symlink_metadata(&path)?;
+        Ok(Self {
+            listener,
+            path,
+            file: (metadata.dev(), metadata.ino()),
+        })
+    }
+
+    /// 获取套接字文件的路径。
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 获取内部的 [`UnixListener`]。
+    pub fn get_ref(&self) -> &UnixListener {
+        &self.listener
+    }
+}
+
+impl Listener for UnixSocketListener {
+    type IO = UnixStream;
+    type Addr = UnixConnectInfo;
+    type Error = std::io::Error;
+
+    async fn accept(&mut self) -> std::io::Result<(Self::IO, Self::Addr)> {
+        let (conn, mut info) = Listener::accept(&mut self.listener).await?;
+        // 通过临时目录绑定时，监听器的本地地址是临时路径。
+        info.local = std::os::unix::net::SocketAddr::from_pathname(&self.path)?.into();
+        Ok((conn, info))
+    }
+}
+
+impl Drop for UnixSocketListener {
+    fn drop(&mut self) {
+        if let Ok(metadata) = std::fs::symlink_metadata(&self.path) {
+            if (metadata.dev(), metadata.ino()) == self.file {
+                let _ = std::fs::remove_file(&self.path);
+            }
+        }
+    }
+}
+
+/// 在套接字文件所在的目录下创建仅当前用户可以访问的临时目录。
+fn private_dir(path: &Path) -> std::io::Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let dir = parent.join(format!(
+        ".boluo-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    Ok(dir)
+}
+
+/// 删除没有进程监听的套接字文件，路径存在但不是套接字文件或者仍有进程监听时不做处理。
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {}
+        Ok(_) => return Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    }
+    match std::os::unix::net::UnixStream::connect(path) {
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    use tokio::net::UnixStream;
+
+    use super::UnixSocketListener;
+    use crate::listener::Listener;
+
+    #[tokio::test]
+    async fn socket_file() {
+        let path = std::env::temp_dir().join(format!("boluo-{}.sock", std::process::id()));
+
+        // 上次运行遗留的套接字文件。
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let mut listener = UnixSocketListener::bind_with_mode(&path, 0o600).unwrap();
+        let metadata = std::fs::metadata(&path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+        let _client = UnixStream::connect(&path).await.unwrap();
+        let (_, info) = listener.accept().await.unwrap();
+        assert_eq!(info.local.as_pathname(), Some(path.as_path()));
+        assert_eq!(info.peer_cred.unwrap().uid(), metadata.uid());
+
+        drop(listener);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn replaced_socket_file() {
+        let path = std::env::temp_dir().join(format!("boluo-{}-replaced.sock", std::process::id()));
+
+        let listener = UnixSocketListener::bind(&path).unwrap();
+        // 其他进程删除并重新创建了套接字文件。
+        std::fs::remove_file(&path).unwrap();
+        let other = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+        drop(listener);
+        assert!(path.exists());
+        drop(other);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
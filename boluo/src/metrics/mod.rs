@@ -0,0 +1,449 @@
+//! 使用 Prometheus 文本格式导出的请求指标。
+
+mod registry;
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use boluo_core::BoxError;
+use boluo_core::body::{Body, Bytes, Frame, HttpBody, SizeHint};
+use boluo_core::http::header::{self, HeaderValue};
+use boluo_core::http::{Method, StatusCode};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response};
+use boluo_core::service::Service;
+
+use self::registry::{InFlightLabels, Registry, RequestLabels};
+use crate::route::MatchedPath;
+
+const DEFAULT_DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+const DEFAULT_SIZE_BUCKETS: [f64; 7] = [
+    100.0,
+    1_000.0,
+    10_000.0,
+    100_000.0,
+    1_000_000.0,
+    10_000_000.0,
+    100_000_000.0,
+];
+
+/// 记录请求指标的中间件，同时也是指标的注册表。
+///
+/// 克隆的实例共享同一个注册表。中间件记录以下指标：
+///
+/// - `http_requests_total`：请求总数。
+/// - `http_request_duration_seconds`：服务返回响应或错误的耗时。
+/// - `http_response_size_bytes`：响应主体的字节数，在响应主体发送完成或被丢弃时记录。
+/// - `http_requests_in_flight`：正在处理的请求数。
+///
+/// 指标使用请求方法 `method`、路由路径 `route` 和状态码类别 `status`（如 `2xx`）作为标签。
+/// 路由路径来自 [`MatchedPath`]，路由器在匹配成功后才会插入，因此应使用
+/// [`Router::with_for_each`] 为路由器内的服务分别应用此中间件。没有匹配的路由路径时，
+/// `route` 标签为空。服务返回错误时，使用服务器添加到请求扩展中的 [`ErrorResponder`] 确定状态码。
+///
+/// 通过 [`Server::metrics`] 将实例交给服务器后，还会导出以下指标：
+///
+/// - `http_server_open_connections`：打开的连接数。
+/// - `http_server_draining_connections`：已经收到关闭信号、正在优雅关闭的连接数，包括
+///   [`GracefulShutdown`] 关闭的连接和达到连接限制后关闭的连接。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::handler::handler_fn;
+/// use boluo::metrics::Metrics;
+/// use boluo::route::Router;
+///
+/// let metrics = Metrics::new();
+///
+/// let router = Router::new()
+///     .route("/users/{id}", handler_fn(|| async { "user" }))
+///     .with_for_each(metrics.clone())
+///     .route("/metrics", metrics.exporter());
+/// ```
+///
+/// [`Router::with_for_each`]: crate::route::Router::with_for_each
+/// [`Server::metrics`]: crate::server::Server::metrics
+/// [`GracefulShutdown`]: crate::server::GracefulShutdown
+/// [`ErrorResponder`]: crate::response::ErrorResponder
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    registry: Arc<Registry>,
+    duration_buckets: Arc<[f64]>,
+    size_buckets: Arc<[f64]>,
+}
+
+impl Metrics {
+    /// 创建使用默认分桶的 [`Metrics`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置 `http_request_duration_seconds` 指标的分桶上界，单位为秒。
+    ///
+    /// 默认值为 `[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1, 2.5, 5, 10]`。
+    pub fn duration_buckets(mut self, buckets: impl Into<Vec<f64>>) -> Self {
+        self.duration_buckets = sort_buckets(buckets.into());
+        self
+    }
+
+    /// 设置 `http_response_size_bytes` 指标的分桶上界，单位为字节。
+    ///
+    /// 默认值为 `[100, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8]`。
+    pub fn size_buckets(mut self, buckets: impl Into<Vec<f64>>) -> Self {
+        self.size_buckets = sort_buckets(buckets.into());
+        self
+    }
+
+    /// 使用 Prometheus 文本格式输出所有指标。
+    pub fn render(&self) -> String {
+        self.registry.render()
+    }
+
+    /// 创建输出指标的服务，可以通过 [`Router::route`] 挂载到指定路径。
+    ///
+    /// [`Router::route`]: crate::route::Router::route
+    pub fn exporter(&self) -> MetricsExporter {
+        MetricsExporter {
+            registry: self.registry.clone(),
+        }
+    }
+
+    /// 记录新打开的连接，返回值被丢弃时记录连接关闭。
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) fn connection(&self) -> ConnectionGuard {
+        self.registry.add_open_connections(1);
+        ConnectionGuard {
+            registry: self.registry.clone(),
+        }
+    }
+
+    /// 记录开始优雅关闭的连接，返回值被丢弃时记录连接关闭。
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(crate) fn draining_connection(&self) -> DrainingGuard {
+        self.registry.add_draining_connections(1);
+        DrainingGuard {
+            registry: self.registry.clone(),
+        }
+    }
+
+    fn record(
+        &self,
+        method: &'static str,
+        route: String,
+        status: StatusCode,
+        start: Instant,
+    ) -> RequestLabels {
+        let labels = (method, route, status_class(status));
+        self.registry
+            .record_request(labels.clone(), start.elapsed(), &self.duration_buckets);
+        labels
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            registry: Default::default(),
+            duration_buckets: Arc::from(DEFAULT_DURATION_BUCKETS),
+            size_buckets: Arc::from(DEFAULT_SIZE_BUCKETS),
+        }
+    }
+}
+
+impl<S> Middleware<S> for Metrics {
+    type Service = MetricsService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        MetricsService {
+            service,
+            metrics: self,
+        }
+    }
+}
+
+/// 中间件 [`Metrics`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct MetricsService<S> {
+    service: S,
+    metrics: Metrics,
+}
+
+impl<S> Service<Request> for MetricsService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let method = method_label(request.method());
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_owned())
+            .unwrap_or_default();
+
+        let responder = crate::response::request_error_responder(&request);
+
+        let _in_flight = InFlight::new(&self.metrics.registry, (method, route.clone()));
+        let start = Instant::now();
+
+        let result = self
+            .service
+            .call(request)
+            .await
+            .map_err(Into::into)
+            .and_then(|response| response.into_response().map_err(Into::into));
+
+        let mut response = match result {
+            Ok(response) => response,
+            Err(error) => {
+                let status = responder
+                    .status(error.as_ref())
+                    .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                self.metrics.record(method, route, status, start);
+                return Err(error);
+            }
+        };
+
+        let labels = self.metrics.record(method, route, response.status(), start);
+        let buckets = &self.metrics.size_buckets;
+        match response.body_mut().size_hint().exact() {
+            Some(size) => {
+                self.metrics.registry.record_size(labels, size, buckets);
+                Ok(response)
+            }
+            None => Ok(response.map(|body| {
+                Body::new(SizeBody {
+                    inner: body,
+                    size: 0,
+                    recorder: Some((self.metrics.registry.clone(), labels, buckets.clone())),
+                })
+            })),
+        }
+    }
+}
+
+/// 输出指标的服务，详情查看 [`Metrics::exporter`]。
+#[derive(Debug, Clone)]
+pub struct MetricsExporter {
+    registry: Arc<Registry>,
+}
+
+impl Service<Request> for MetricsExporter {
+    type Response = Response;
+    type Error = std::convert::Infallible;
+
+    async fn call(&self, _: Request) -> Result<Self::Response, Self::Error> {
+        let mut response = Response::new(Body::from(self.registry.render()));
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+        );
+        Ok(response)
+    }
+}
+
+/// 连接的记录，被丢弃时记录连接关闭。
+#[cfg(any(feature = "http1", feature = "http2"))]
+pub(crate) struct ConnectionGuard {
+    registry: Arc<Registry>,
+}
+
+#[cfg(any(feature = "http1", feature = "http2"))]
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.registry.add_open_connections(-1);
+    }
+}
+
+/// 正在优雅关闭的连接的记录，被丢弃时记录连接关闭。
+#[cfg(any(feature = "http1", feature = "http2"))]
+pub(crate) struct DrainingGuard {
+    registry: Arc<Registry>,
+}
+
+#[cfg(any(feature = "http1", feature = "http2"))]
+impl Drop for DrainingGuard {
+    fn drop(&mut self) {
+        self.registry.add_draining_connections(-1);
+    }
+}
+
+/// 正在处理的请求，被丢弃时减少计数。
+struct InFlight<'a> {
+    registry: &'a Registry,
+    labels: InFlightLabels,
+}
+
+impl<'a> InFlight<'a> {
+    fn new(registry: &'a Registry, labels: InFlightLabels) -> Self {
+        registry.add_in_flight(&labels, 1);
+        Self { registry, labels }
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.registry.add_in_flight(&self.labels, -1);
+    }
+}
+
+type SizeRecorder = (Arc<Registry>, RequestLabels, Arc<[f64]>);
+
+/// 统计响应主体字节数的主体，被丢弃时记录字节数。
+struct SizeBody {
+    inner: Body,
+    size: u64,
+    recorder: Option<SizeRecorder>,
+}
+
+impl HttpBody for SizeBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_frame(cx);
+        if let Poll::Ready(Some(Ok(frame))) = &poll {
+            if let Some(data) = frame.data_ref() {
+                this.size += data.len() as u64;
+            }
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for SizeBody {
+    fn drop(&mut self) {
+        if let Some((registry, labels, buckets)) = self.recorder.take() {
+            registry.record_size(labels, self.size, &buckets);
+        }
+    }
+}
+
+fn sort_buckets(mut buckets: Vec<f64>) -> Arc<[f64]> {
+    buckets.retain(|bound| bound.is_finite());
+    buckets.sort_by(f64::total_cmp);
+    buckets.dedup();
+    Arc::from(buckets)
+}
+
+/// 将请求方法转换为标签，非标准方法统一为 `OTHER` 以限制标签的基数。
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        Method::CONNECT => "CONNECT",
+        Method::PATCH => "PATCH",
+        Method::TRACE => "TRACE",
+        _ => "OTHER",
+    }
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() / 100 {
+        1 => "1xx",
+        2 => "2xx",
+        3 => "3xx",
+        4 => "4xx",
+        _ => "5xx",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::ErrorResponder;
+    use boluo_core::service::Service;
+
+    use super::Metrics;
+    use crate::extract::JsonError;
+    use crate::route::Router;
+
+    #[tokio::test]
+    async fn record_and_render() {
+        let metrics = Metrics::new();
+        let router = Router::new()
+            .route("/users/{id}", handler_fn(|| async { "user" }))
+            .route(
+                "/error",
+                handler_fn(|| async { Err::<(), _>(JsonError::UnsupportedContentType) }),
+            )
+            .with_for_each(metrics.clone())
+            .route("/metrics", metrics.exporter());
+
+        for path in ["/users/1", "/users/2", "/error"] {
+            let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+            let _ = router.call(request).await;
+        }
+
+        let request = Request::builder()
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        let text = response.into_body().to_bytes().await.unwrap();
+        let text = std::str::from_utf8(&text).unwrap();
+
+        assert!(text.contains(
+            "http_requests_total{method=\"GET\",route=\"/users/{id}\",status=\"2xx\"} 2\n"
+        ));
+        assert!(
+            text.contains(
+                "http_requests_total{method=\"GET\",route=\"/error\",status=\"4xx\"} 1\n"
+            )
+        );
+        assert!(text.contains(
+            "http_response_size_bytes_sum{method=\"GET\",route=\"/users/{id}\",status=\"2xx\"} 8\n"
+        ));
+        assert!(text.contains("http_requests_in_flight{method=\"GET\",route=\"/users/{id}\"} 0\n"));
+        assert!(text.contains("http_server_open_connections 0\n"));
+    }
+
+    #[tokio::test]
+    async fn request_error_responder() {
+        let metrics = Metrics::new();
+        let service = metrics.clone().transform(handler_fn(|| async {
+            Err::<(), _>(JsonError::UnsupportedContentType)
+        }));
+
+        // 服务器的 `ErrorResponder` 没有注册 `JsonError`，状态码为 500。
+        let mut request = Request::default();
+        request.extensions_mut().insert(ErrorResponder::new());
+        let _ = service.call(request).await;
+
+        assert!(
+            metrics
+                .render()
+                .contains("http_requests_total{method=\"GET\",route=\"\",status=\"5xx\"} 1\n")
+        );
+    }
+}
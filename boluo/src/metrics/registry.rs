@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// 请求指标的标签：请求方法、路由路径和状态码类别。
+pub(super) type RequestLabels = (&'static str, String, &'static str);
+
+/// 进行中请求的标签：请求方法和路由路径。
+pub(super) type InFlightLabels = (&'static str, String);
+
+/// 保存所有指标的注册表。
+#[derive(Debug, Default)]
+pub(super) struct Registry {
+    requests: Mutex<BTreeMap<RequestLabels, RequestStats>>,
+    sizes: Mutex<BTreeMap<RequestLabels, Histogram>>,
+    in_flight: Mutex<BTreeMap<InFlightLabels, i64>>,
+    open_connections: AtomicI64,
+    draining_connections: AtomicI64,
+}
+
+#[derive(Debug)]
+struct RequestStats {
+    count: u64,
+    duration: Histogram,
+}
+
+#[derive(Debug)]
+struct Histogram {
+    bounds: Arc<[f64]>,
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(bounds: Arc<[f64]>) -> Self {
+        Self {
+            buckets: vec![0; bounds.len()],
+            bounds,
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if let Some(index) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl Registry {
+    pub(super) fn record_request(
+        &self,
+        labels: RequestLabels,
+        duration: Duration,
+        buckets: &Arc<[f64]>,
+    ) {
+        let mut requests = lock(&self.requests);
+        let stats = requests.entry(labels).or_insert_with(|| RequestStats {
+            count: 0,
+            duration: Histogram::new(buckets.clone()),
+        });
+        stats.count += 1;
+        stats.duration.observe(duration.as_secs_f64());
+    }
+
+    pub(super) fn record_size(&self, labels: RequestLabels, size: u64, buckets: &Arc<[f64]>) {
+        lock(&self.sizes)
+            .entry(labels)
+            .or_insert_with(|| Histogram::new(buckets.clone()))
+            .observe(size as f64);
+    }
+
+    pub(super) fn add_in_flight(&self, labels: &InFlightLabels, delta: i64) {
+        let mut in_flight = lock(&self.in_flight);
+        match in_flight.get_mut(labels) {
+            Some(value) => *value += delta,
+            None => {
+                in_flight.insert(labels.clone(), delta);
+            }
+        }
+    }
+
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(super) fn add_open_connections(&self, delta: i64) {
+        self.open_connections.fetch_add(delta, Ordering::Relaxed);
+    }
+
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    pub(super) fn add_draining_connections(&self, delta: i64) {
+        self.draining_connections
+            .fetch_add(delta, Ordering::Relaxed);
+    }
+
+    /// 使用 Prometheus 文本格式输出所有指标。
+    pub(super) fn render(&self) -> String {
+        let mut out = String::new();
+
+        {
+            let requests = lock(&self.requests);
+            header(
+                &mut out,
+                "http_requests_total",
+                "counter",
+                "Total number of HTTP requests.",
+            );
+            for (labels, stats) in requests.iter() {
+                sample(
+                    &mut out,
+                    "http_requests_total",
+                    &request_labels(labels),
+                    stats.count,
+                );
+            }
+
+            header(
+                &mut out,
+                "http_request_duration_seconds",
+                "histogram",
+                "HTTP request latency in seconds.",
+            );
+            for (labels, stats) in requests.iter() {
+                histogram(
+                    &mut out,
+                    "http_request_duration_seconds",
+                    &request_labels(labels),
+                    &stats.duration,
+                );
+            }
+        }
+
+        header(
+            &mut out,
+            "http_response_size_bytes",
+            "histogram",
+            "HTTP response body size in bytes.",
+        );
+        for (labels, sizes) in lock(&self.sizes).iter() {
+            histogram(
+                &mut out,
+                "http_response_size_bytes",
+                &request_labels(labels),
+                sizes,
+            );
+        }
+
+        header(
+            &mut out,
+            "http_requests_in_flight",
+            "gauge",
+            "Number of HTTP requests currently being processed.",
+        );
+        for ((method, route), value) in lock(&self.in_flight).iter() {
+            let labels = format!(
+                "method=\"{method}\",route=\"{}\"",
+                escape_label_value(route)
+            );
+            sample(&mut out, "http_requests_in_flight", &labels, value);
+        }
+
+        let open = self.open_connections.load(Ordering::Relaxed);
+        let draining = self.draining_connections.load(Ordering::Relaxed);
+        header(
+            &mut out,
+            "http_server_open_connections",
+            "gauge",
+            "Number of open connections.",
+        );
+        sample(&mut out, "http_server_open_connections", "", open);
+        header(
+            &mut out,
+            "http_server_draining_connections",
+            "gauge",
+            "Number of connections being gracefully closed.",
+        );
+        sample(&mut out, "http_server_draining_connections", "", draining);
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    if labels.is_empty() {
+        let _ = writeln!(out, "{name} {value}");
+    } else {
+        let _ = writeln!(out, "{name}{{{labels}}} {value}");
+    }
+}
+
+fn histogram(out: &mut String, name: &str, labels: &str, histogram: &Histogram) {
+    let mut cumulative = 0;
+    for (bound, count) in histogram.bounds.iter().zip(&histogram.buckets) {
+        cumulative += count;
+        let labels = format!("{labels},le=\"{bound}\"");
+        sample(out, &format!("{name}_bucket"), &labels, cumulative);
+    }
+    let labels_inf = format!("{labels},le=\"+Inf\"");
+    sample(out, &format!("{name}_bucket"), &labels_inf, histogram.count);
+    sample(out, &format!("{name}_sum"), labels, histogram.sum);
+    sample(out, &format!("{name}_count"), labels, histogram.count);
+}
+
+fn request_labels((method, route, status): &RequestLabels) -> String {
+    format!(
+        "method=\"{method}\",route=\"{}\",status=\"{status}\"",
+        escape_label_value(route)
+    )
+}
+
+fn escape_label_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
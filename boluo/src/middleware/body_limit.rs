@@ -0,0 +1,191 @@
+use boluo_core::BoxError;
+use boluo_core::body::Body;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::service::Service;
+
+/// 限制请求主体长度的中间件。
+///
+/// 限制请求主体可以读取的数据长度，提取器、[`Multipart`] 和 [`BodyDataStream`] 读取超过限制的数据时都会得到
+/// [`LengthLimitError`]，服务器将其转换为 `413 Payload Too Large` 响应。请求主体的 `Content-Length`
+/// 超过限制时，读取第一块数据前就会返回错误。
+///
+/// 内层的 [`BodyLimit`] 会替换外层设置的限制，可以为单个路由设置不同的限制。
+///
+/// 请求主体没有设置限制时，[`Json`]、[`Form`] 和 [`Multipart`] 使用 [`BodyLimit::DEFAULT_LIMIT`]
+/// 限制读取的数据长度，其他提取器不限制数据长度。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::handler::handler_fn;
+/// use boluo::middleware::BodyLimit;
+/// use boluo::route::{Router, post};
+/// use boluo::service::ServiceExt;
+///
+/// let upload = handler_fn(|body: boluo::body::Bytes| async move { body.len().to_string() });
+///
+/// let router = Router::new()
+///     .route("/echo", post(handler_fn(|body: String| async move { body })))
+///     .route("/upload", post(upload.with(BodyLimit::new(16 * 1024 * 1024))))
+///     .with(BodyLimit::new(64 * 1024));
+/// ```
+///
+/// [`LengthLimitError`]: boluo_core::body::LengthLimitError
+/// [`Json`]: crate::extract::Json
+/// [`Form`]: crate::extract::Form
+/// [`Multipart`]: crate::multipart::Multipart
+/// [`BodyDataStream`]: boluo_core::body::BodyDataStream
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit {
+    limit: usize,
+}
+
+impl BodyLimit {
+    /// 请求主体没有设置限制时，[`Json`]、[`Form`] 和 [`Multipart`] 使用的默认限制，为 2 MiB。
+    ///
+    /// [`Json`]: crate::extract::Json
+    /// [`Form`]: crate::extract::Form
+    /// [`Multipart`]: crate::multipart::Multipart
+    pub const DEFAULT_LIMIT: usize = 2 * 1024 * 1024;
+
+    /// 创建限制请求主体长度的中间件，`limit` 为允许的最大字节数。
+    pub fn new(limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl<S> Middleware<S> for BodyLimit {
+    type Service = BodyLimitService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        BodyLimitService {
+            service,
+            limit: self.limit,
+        }
+    }
+}
+
+/// 中间件 [`BodyLimit`] 返回的服务。
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimitService<S> {
+    service: S,
+    limit: usize,
+}
+
+impl<S> Service<Request> for BodyLimitService<S>
+where
+    S: Service<Request>,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
+        let body = std::mem::take(request.body_mut());
+        *request.body_mut() = body.limit(self.limit);
+
+        self.service.call(request).await.map_err(Into::into)
+    }
+}
+
+/// 取出请求主体，主体没有设置限制时使用 [`BodyLimit::DEFAULT_LIMIT`]。
+pub(crate) fn take_limited_body(request: &mut Request) -> Body {
+    let body = std::mem::take(request.body_mut());
+    match body.length_limit() {
+        Some(_) => body,
+        None => body.limit(BodyLimit::DEFAULT_LIMIT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::{Body, Bytes, LengthLimitError};
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{StatusCode, header};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::ErrorResponse;
+    use boluo_core::service::Service;
+
+    use super::BodyLimit;
+    use crate::extract::{Json, JsonError};
+
+    fn request(body: &'static str) -> Request {
+        Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn request_with_length(body: &'static str) -> Request {
+        let mut request = request(body);
+        request
+            .headers_mut()
+            .insert(header::CONTENT_LENGTH, body.len().into());
+        request
+    }
+
+    #[tokio::test]
+    async fn limit_buffered_body() {
+        let service = BodyLimit::new(4).transform(handler_fn(|body: Bytes| async move { body }));
+
+        assert!(service.call(request("1234")).await.is_ok());
+
+        let error = service.call(request("12345")).await.unwrap_err();
+        assert!(error.is::<LengthLimitError>());
+
+        let stream = Body::from_data_stream(futures_util::stream::iter([
+            Ok::<_, std::convert::Infallible>("123"),
+            Ok("45"),
+        ]));
+        let error = service.call(Request::new(stream)).await.unwrap_err();
+        assert!(error.is::<LengthLimitError>());
+    }
+
+    #[tokio::test]
+    async fn reject_content_length() {
+        let service = BodyLimit::new(4).transform(handler_fn(|body: Bytes| async move { body }));
+        assert!(service.call(request_with_length("1234")).await.is_ok());
+
+        let error = service
+            .call(request_with_length("12345"))
+            .await
+            .unwrap_err();
+        assert!(error.is::<LengthLimitError>());
+    }
+
+    #[tokio::test]
+    async fn inner_limit_replaces_outer() {
+        let handler = handler_fn(|Json(value): Json<u64>| async move { value.to_string() });
+
+        let service = BodyLimit::new(2).transform(handler);
+        let error = service.call(request("1234567")).await.unwrap_err();
+        let error = error.downcast_ref::<JsonError>().unwrap();
+        assert_eq!(error.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let service = BodyLimit::new(2).transform(BodyLimit::new(8).transform(handler));
+        assert!(service.call(request("1234567")).await.is_ok());
+        assert!(service.call(request_with_length("1234567")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn default_limit() {
+        let handler =
+            handler_fn(|Json(value): Json<String>| async move { value.len().to_string() });
+        let body = format!("\"{}\"", "a".repeat(BodyLimit::DEFAULT_LIMIT));
+        let request = || {
+            Request::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.clone()))
+                .unwrap()
+        };
+
+        let error = handler.call(request()).await.unwrap_err();
+        let error = error.downcast_ref::<JsonError>().unwrap();
+        assert_eq!(error.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let service = BodyLimit::new(BodyLimit::DEFAULT_LIMIT * 2).transform(handler);
+        assert!(service.call(request()).await.is_ok());
+    }
+}
@@ -0,0 +1,235 @@
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use boluo_core::BoxError;
+use boluo_core::http::StatusCode;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+use boluo_core::service::Service;
+use futures_util::FutureExt;
+
+type OnPanic = Arc<dyn Fn(&PanicError) -> Response + Send + Sync>;
+
+/// 捕获服务恐慌的中间件。
+///
+/// 服务在处理请求时发生恐慌，中间件会捕获恐慌并返回携带恐慌信息的 [`PanicError`]，
+/// 日志等中间件可以像处理其他错误一样记录它。错误默认对应 `500 INTERNAL_SERVER_ERROR` 响应，
+/// 可以通过 [`CatchPanic::status`] 和 [`CatchPanic::response`] 自定义响应。
+///
+/// 中间件只能捕获服务返回响应之前发生的恐慌，不包括读取响应主体时发生的恐慌。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::http::StatusCode;
+/// use boluo::middleware::CatchPanic;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// let catch_panic = CatchPanic::new()
+///     .status(StatusCode::SERVICE_UNAVAILABLE)
+///     .response(|_| "something went wrong");
+///
+/// let router = Router::new().with(catch_panic);
+/// ```
+#[derive(Clone)]
+pub struct CatchPanic {
+    status: StatusCode,
+    on_panic: Option<OnPanic>,
+}
+
+impl CatchPanic {
+    /// 创建 [`CatchPanic`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置恐慌对应的响应状态码，默认为 `500 INTERNAL_SERVER_ERROR`。
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// 设置将恐慌转换为响应的函数。
+    ///
+    /// 响应的状态码始终为 [`CatchPanic::status`] 设置的状态码。函数返回值转换为响应失败时，
+    /// 返回只有状态码的响应。
+    pub fn response<F, R>(mut self, f: F) -> Self
+    where
+        F: Fn(&PanicError) -> R + Send + Sync + 'static,
+        R: IntoResponse,
+    {
+        self.on_panic = Some(Arc::new(move |error| {
+            f(error)
+                .into_response()
+                .unwrap_or_else(|_| error.status.into_response_always())
+        }));
+        self
+    }
+}
+
+impl Default for CatchPanic {
+    fn default() -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            on_panic: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for CatchPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CatchPanic")
+            .field("status", &self.status)
+            .field("on_panic", &self.on_panic.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl<S> Middleware<S> for CatchPanic {
+    type Service = CatchPanicService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        CatchPanicService {
+            service,
+            catch_panic: self,
+        }
+    }
+}
+
+/// 中间件 [`CatchPanic`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct CatchPanicService<S> {
+    service: S,
+    catch_panic: CatchPanic,
+}
+
+impl<S> Service<Request> for CatchPanicService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        match AssertUnwindSafe(self.service.call(request))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result
+                .map_err(Into::into)?
+                .into_response()
+                .map_err(Into::into),
+            Err(payload) => Err(PanicError {
+                message: panic_message(payload.as_ref()),
+                status: self.catch_panic.status,
+                on_panic: self.catch_panic.on_panic.clone(),
+            }
+            .into()),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> Arc<str> {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        Arc::from(*message)
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        Arc::from(message.as_str())
+    } else {
+        Arc::from("Box<dyn Any>")
+    }
+}
+
+/// 服务发生恐慌的错误。
+///
+/// 错误对应的响应状态码由 [`CatchPanic::status`] 设置，设置了 [`CatchPanic::response`]
+/// 时由该函数生成响应。
+#[derive(Clone)]
+pub struct PanicError {
+    message: Arc<str>,
+    status: StatusCode,
+    on_panic: Option<OnPanic>,
+}
+
+impl PanicError {
+    /// 获取恐慌信息。
+    ///
+    /// 恐慌的负载不是字符串时，返回 `"Box<dyn Any>"`。
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Debug for PanicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PanicError")
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for PanicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "service panicked ({})", self.message)
+    }
+}
+
+impl std::error::Error for PanicError {}
+
+impl ErrorResponse for PanicError {
+    fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    fn to_response(&self) -> Response {
+        let Some(on_panic) = &self.on_panic else {
+            return self.status.into_response_always();
+        };
+        let mut response = on_panic(self);
+        *response.status_mut() = self.status;
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::StatusCode;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::ErrorResponse;
+    use boluo_core::service::Service;
+
+    use super::{CatchPanic, PanicError};
+    use crate::response::error_responder;
+
+    #[tokio::test]
+    async fn catch_panic() {
+        let service = CatchPanic::new()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .response(|error| error.message().to_owned())
+            .transform(handler_fn(|| async {
+                if true {
+                    panic!("boom");
+                }
+            }));
+
+        let error = service.call(Request::new(Body::empty())).await.unwrap_err();
+        let panic_error = error.downcast_ref::<PanicError>().unwrap();
+        assert_eq!(panic_error.message(), "boom");
+        assert_eq!(panic_error.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = error_responder().respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "boom");
+
+        // 每次转换都会重新生成响应。
+        let response = error_responder().respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
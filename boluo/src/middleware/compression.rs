@@ -0,0 +1,440 @@
+use boluo_core::BoxError;
+use boluo_core::body::HttpBody;
+use boluo_core::http::StatusCode;
+use boluo_core::http::header::{self, HeaderMap, HeaderValue};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response};
+use boluo_core::service::Service;
+
+use super::encoding::{self, Encoding};
+
+/// 压缩响应主体的中间件。
+///
+/// 根据请求的 `Accept-Encoding` 标头选择 `br`、`zstd`、`gzip` 或 `deflate` 编码，以流的方式压缩响应主体，
+/// 并为响应添加 `Vary: accept-encoding` 标头。压缩后的主体与原主体的字节不同，强 `ETag` 会被转换为弱 `ETag`。
+///
+/// 以下响应不会被压缩：
+///
+/// - 已经包含 `Content-Encoding` 标头的响应。
+/// - `206 Partial Content` 等没有完整主体的响应。
+/// - 主体长度已知且小于 [`Compression::min_size`] 的响应。
+/// - 内容类型为 `text/event-stream` 的响应，除非启用了 [`Compression::compress_sse`]。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::middleware::Compression;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// let router = Router::new().with(Compression::new().deflate(false));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Compression {
+    gzip: bool,
+    deflate: bool,
+    br: bool,
+    zstd: bool,
+    min_size: u64,
+    compress_sse: bool,
+}
+
+impl Compression {
+    /// 创建启用所有编码的 [`Compression`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置是否启用 `gzip` 编码。
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.gzip = enable;
+        self
+    }
+
+    /// 设置是否启用 `deflate` 编码。
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.deflate = enable;
+        self
+    }
+
+    /// 设置是否启用 `br` 编码。
+    pub fn br(mut self, enable: bool) -> Self {
+        self.br = enable;
+        self
+    }
+
+    /// 设置是否启用 `zstd` 编码。
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.zstd = enable;
+        self
+    }
+
+    /// 设置压缩的最小主体长度，长度未知的主体总是会被压缩。
+    ///
+    /// 默认值为 32 字节。
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// 设置是否压缩服务器发送事件（`text/event-stream`）。
+    ///
+    /// 默认不压缩。
+    pub fn compress_sse(mut self, enable: bool) -> Self {
+        self.compress_sse = enable;
+        self
+    }
+
+    fn is_enabled(&self, encoding: Encoding) -> bool {
+        match encoding {
+            Encoding::Gzip => self.gzip,
+            Encoding::Deflate => self.deflate,
+            Encoding::Br => self.br,
+            Encoding::Zstd => self.zstd,
+        }
+    }
+
+    /// 根据 `Accept-Encoding` 标头选择编码，权重相同时按照 `br`、`zstd`、`gzip`、`deflate` 的顺序选择。
+    fn negotiate(&self, headers: &HeaderMap) -> Option<Encoding> {
+        let mut explicit = Vec::new();
+        let mut wildcard = None;
+        for value in headers.get_all(header::ACCEPT_ENCODING) {
+            let Ok(value) = value.to_str() else {
+                continue;
+            };
+            for item in value.split(',') {
+                let mut params = item.split(';');
+                let name = params.next().unwrap_or_default().trim();
+                let quality = params.find_map(parse_quality).unwrap_or(1000);
+                if name == "*" {
+                    wildcard = Some(quality);
+                } else if let Some(encoding) = Encoding::parse(name) {
+                    explicit.push((encoding, quality));
+                }
+            }
+        }
+
+        // 按照优先级升序排列，`max_by_key` 在权重相同时返回最后一个元素。
+        [
+            Encoding::Deflate,
+            Encoding::Gzip,
+            Encoding::Zstd,
+            Encoding::Br,
+        ]
+        .into_iter()
+        .filter(|encoding| self.is_enabled(*encoding))
+        .filter_map(|encoding| {
+            let quality = explicit
+                .iter()
+                .find(|(e, _)| *e == encoding)
+                .map(|(_, quality)| *quality)
+                .or(wildcard)?;
+            (quality > 0).then_some((encoding, quality))
+        })
+        .max_by_key(|(_, quality)| *quality)
+        .map(|(encoding, _)| encoding)
+    }
+
+    fn compress(&self, mut response: Response, encoding: Option<Encoding>) -> Response {
+        let status = response.status();
+        if status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::PARTIAL_CONTENT
+            || status == StatusCode::NOT_MODIFIED
+        {
+            return response;
+        }
+
+        let headers = response.headers();
+        if headers.contains_key(header::CONTENT_ENCODING)
+            || headers.contains_key(header::CONTENT_RANGE)
+            || (!self.compress_sse && is_event_stream(headers))
+        {
+            return response;
+        }
+        if response
+            .body_mut()
+            .size_hint()
+            .exact()
+            .is_some_and(|len| len < self.min_size)
+        {
+            return response;
+        }
+
+        response.headers_mut().append(
+            header::VARY,
+            HeaderValue::from_static(header::ACCEPT_ENCODING.as_str()),
+        );
+
+        let Some(encoding) = encoding else {
+            return response;
+        };
+
+        let headers = response.headers_mut();
+        headers.remove(header::CONTENT_LENGTH);
+        headers.remove(header::ACCEPT_RANGES);
+        headers.insert(
+            header::CONTENT_ENCODING,
+            HeaderValue::from_static(encoding.as_str()),
+        );
+        if let Some(etag) = headers.get(header::ETAG).and_then(weaken_etag) {
+            headers.insert(header::ETAG, etag);
+        }
+        response.map(|body| encoding::encode(body, encoding))
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            deflate: true,
+            br: true,
+            zstd: true,
+            min_size: 32,
+            compress_sse: false,
+        }
+    }
+}
+
+impl<S> Middleware<S> for Compression {
+    type Service = CompressionService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        CompressionService {
+            service,
+            compression: self,
+        }
+    }
+}
+
+/// 中间件 [`Compression`] 返回的服务。
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionService<S> {
+    service: S,
+    compression: Compression,
+}
+
+impl<S> Service<Request> for CompressionService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let encoding = self.compression.negotiate(request.headers());
+        let response = self
+            .service
+            .call(request)
+            .await
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)?;
+        Ok(self.compression.compress(response, encoding))
+    }
+}
+
+/// 解析 `q=` 参数，返回千分制的权重。
+fn parse_quality(param: &str) -> Option<u16> {
+    let (name, value) = param.split_once('=')?;
+    if !name.trim().eq_ignore_ascii_case("q") {
+        return None;
+    }
+    let quality = value.trim().parse::<f32>().ok()?;
+    if !(0.0..=1.0).contains(&quality) {
+        return None;
+    }
+    Some((quality * 1000.0) as u16)
+}
+
+/// 将强 `ETag` 转换为弱 `ETag`，已经是弱 `ETag` 时返回 `None`。
+fn weaken_etag(etag: &HeaderValue) -> Option<HeaderValue> {
+    let etag = etag.as_bytes();
+    if etag.starts_with(b"W/") {
+        return None;
+    }
+    HeaderValue::from_bytes(&[b"W/", etag].concat()).ok()
+}
+
+fn is_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with(mime::TEXT_EVENT_STREAM.as_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::header::{self, HeaderMap};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::Response;
+    use boluo_core::service::Service;
+    use futures_util::StreamExt;
+    use tokio::io::AsyncReadExt;
+
+    use super::Compression;
+    use crate::middleware::encoding::Encoding;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn negotiate() {
+        let compression = Compression::new();
+        assert_eq!(
+            compression.negotiate(&accept("gzip, deflate, br, zstd")),
+            Some(Encoding::Br)
+        );
+        assert_eq!(
+            compression.negotiate(&accept("gzip;q=1.0, br;q=0.5")),
+            Some(Encoding::Gzip)
+        );
+        assert_eq!(
+            compression.negotiate(&accept("*;q=0.5, br;q=0")),
+            Some(Encoding::Zstd)
+        );
+        assert_eq!(compression.negotiate(&accept("identity")), None);
+        assert_eq!(
+            compression.br(false).negotiate(&accept("br, gzip")),
+            Some(Encoding::Gzip)
+        );
+    }
+
+    #[tokio::test]
+    async fn compress_stream() {
+        let text = "hello world ".repeat(100);
+        let chunks = text.clone().into_bytes();
+        let service = Compression::new().transform(handler_fn(move || {
+            let chunks = chunks.clone();
+            async move {
+                let stream = futures_util::stream::iter(
+                    chunks
+                        .chunks(100)
+                        .map(|chunk| Ok::<_, std::convert::Infallible>(chunk.to_vec()))
+                        .collect::<Vec<_>>(),
+                );
+                Response::new(Body::from_data_stream(stream))
+            }
+        }));
+
+        let request = Request::builder()
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::VARY], "accept-encoding");
+
+        let compressed = response.into_body().to_bytes().await.unwrap();
+        let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(&compressed[..]);
+        let mut decompressed = String::new();
+        decoder.read_to_string(&mut decompressed).await.unwrap();
+        assert_eq!(decompressed, text);
+    }
+
+    #[tokio::test]
+    async fn skip_small_and_sse() {
+        let service = Compression::new().transform(handler_fn(|request: Request| async move {
+            let mut response = Response::new(Body::from("x".repeat(64)));
+            if request.uri().path() == "/sse" {
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_TYPE, "text/event-stream".parse().unwrap());
+            }
+            if request.uri().path() == "/small" {
+                *response.body_mut() = Body::from("x");
+            }
+            response
+        }));
+
+        for path in ["/sse", "/small"] {
+            let request = Request::builder()
+                .uri(path)
+                .header(header::ACCEPT_ENCODING, "gzip")
+                .body(Body::empty())
+                .unwrap();
+            let response = service.call(request).await.unwrap();
+            assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+        }
+    }
+
+    #[tokio::test]
+    async fn deflate_and_flush() {
+        let service = Compression::new().transform(handler_fn(|request: Request| async move {
+            let pending = request.uri().path() == "/pending";
+            let stream = futures_util::stream::iter(0..100).then(move |_| async move {
+                if pending {
+                    tokio::task::yield_now().await;
+                }
+                Ok::<_, std::convert::Infallible>("hello world ".repeat(10))
+            });
+            Response::new(Body::from_data_stream(stream))
+        }));
+
+        for path in ["/ready", "/pending"] {
+            let request = Request::builder()
+                .uri(path)
+                .header(header::ACCEPT_ENCODING, "deflate")
+                .body(Body::empty())
+                .unwrap();
+            let response = service.call(request).await.unwrap();
+            assert_eq!(response.headers()[header::CONTENT_ENCODING], "deflate");
+
+            let mut chunks = response.into_body().into_data_stream();
+            let mut compressed = Vec::new();
+            let mut count = 0;
+            while let Some(chunk) = chunks.next().await {
+                compressed.extend_from_slice(&chunk.unwrap());
+                count += 1;
+            }
+            // 连续到达的数据一起压缩，需要等待的数据每块都会刷新编码器。
+            if path == "/ready" {
+                assert!(count < 5, "{count}");
+            } else {
+                assert!(count >= 100, "{count}");
+            }
+
+            // `deflate` 编码使用 zlib 格式。
+            let mut decoder = async_compression::tokio::bufread::ZlibDecoder::new(&compressed[..]);
+            let mut decompressed = String::new();
+            decoder.read_to_string(&mut decompressed).await.unwrap();
+            assert_eq!(decompressed, "hello world ".repeat(1000));
+        }
+    }
+
+    #[tokio::test]
+    async fn weaken_etag() {
+        let service = Compression::new().transform(handler_fn(|request: Request| async move {
+            let etag = request.uri().path().trim_start_matches('/').to_owned();
+            let mut response = Response::new(Body::from("x".repeat(64)));
+            response
+                .headers_mut()
+                .insert(header::ETAG, etag.parse().unwrap());
+            response
+        }));
+
+        for (path, encoding, etag) in [
+            ("/\"abc\"", "gzip", "W/\"abc\""),
+            ("/W/\"abc\"", "gzip", "W/\"abc\""),
+            ("/\"abc\"", "identity", "\"abc\""),
+        ] {
+            let request = Request::builder()
+                .uri(path)
+                .header(header::ACCEPT_ENCODING, encoding)
+                .body(Body::empty())
+                .unwrap();
+            let response = service.call(request).await.unwrap();
+            assert_eq!(response.headers()[header::ETAG], etag);
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use boluo_core::BoxError;
+use boluo_core::http::StatusCode;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+use boluo_core::service::Service;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// 限制同时处理的请求数的中间件。
+///
+/// 正在处理的请求数达到上限时，新请求会等待其他请求完成。默认等待的请求数没有限制，
+/// 可以通过 [`ConcurrencyLimit::queue`] 设置等待队列的长度，队列已满时新请求会被拒绝并返回
+/// [`OverloadedError`]，服务器将其转换为 `503 SERVICE_UNAVAILABLE` 响应。
+///
+/// 请求在内部服务返回响应后释放配额，不包括发送响应主体的时间。每次调用 [`Middleware::transform`]
+/// 都会创建独立的配额，通过 [`Router::with_for_each`] 应用时每个路由独立限制，
+/// 使用 [`Server::concurrency_limit`] 可以限制整个服务器。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::handler::handler_fn;
+/// use boluo::middleware::ConcurrencyLimit;
+/// use boluo::route::{Router, post};
+/// use boluo::service::ServiceExt;
+///
+/// let export = post(handler_fn(|| async { "ok" })).with(ConcurrencyLimit::new(4).queue(16));
+///
+/// let router = Router::new().route("/export", export);
+/// ```
+///
+/// [`Router::with_for_each`]: crate::route::Router::with_for_each
+/// [`Server::concurrency_limit`]: crate::server::Server::concurrency_limit
+#[derive(Debug, Clone, Copy)]
+pub struct ConcurrencyLimit {
+    max: usize,
+    queue: Option<usize>,
+}
+
+impl ConcurrencyLimit {
+    /// 创建最多同时处理 `max` 个请求的 [`ConcurrencyLimit`]。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `max` 为零或超过 [`Semaphore::MAX_PERMITS`] 时会触发 panic。
+    pub fn new(max: usize) -> Self {
+        assert!(
+            max > 0 && max <= Semaphore::MAX_PERMITS,
+            "concurrency limit must be between 1 and {}",
+            Semaphore::MAX_PERMITS
+        );
+        Self { max, queue: None }
+    }
+
+    /// 设置等待队列的长度，队列已满时新请求会被立即拒绝。
+    pub fn queue(mut self, len: usize) -> Self {
+        self.queue = Some(len);
+        self
+    }
+}
+
+impl<S> Middleware<S> for ConcurrencyLimit {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        ConcurrencyLimitService {
+            service,
+            limiter: Arc::new(Limiter {
+                semaphore: Semaphore::new(self.max),
+                queue: self.queue,
+                waiting: AtomicUsize::new(0),
+            }),
+        }
+    }
+}
+
+/// 中间件 [`ConcurrencyLimit`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitService<S> {
+    service: S,
+    limiter: Arc<Limiter>,
+}
+
+#[derive(Debug)]
+struct Limiter {
+    semaphore: Semaphore,
+    queue: Option<usize>,
+    waiting: AtomicUsize,
+}
+
+impl Limiter {
+    async fn acquire(&self) -> Result<SemaphorePermit<'_>, OverloadedError> {
+        if let Ok(permit) = self.semaphore.try_acquire() {
+            return Ok(permit);
+        }
+
+        let _waiting = match self.queue {
+            Some(queue) => {
+                let waiting = self.waiting.fetch_add(1, Ordering::AcqRel);
+                let guard = Waiting(&self.waiting);
+                if waiting >= queue {
+                    return Err(OverloadedError);
+                }
+                Some(guard)
+            }
+            None => None,
+        };
+        Ok(self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed"))
+    }
+}
+
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<S> Service<Request> for ConcurrencyLimitService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let _permit = self.limiter.acquire().await?;
+        self.service
+            .call(request)
+            .await
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)
+    }
+}
+
+/// 服务过载的错误。
+///
+/// 错误对应的响应为 `503 SERVICE_UNAVAILABLE`。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverloadedError;
+
+impl std::fmt::Display for OverloadedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("service overloaded")
+    }
+}
+
+impl std::error::Error for OverloadedError {}
+
+impl ErrorResponse for OverloadedError {
+    fn status(&self) -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+    use tokio::sync::Semaphore;
+
+    use super::{ConcurrencyLimit, OverloadedError};
+
+    #[tokio::test]
+    async fn queue() {
+        let gate = Arc::new(Semaphore::new(0));
+        let service = Arc::new(ConcurrencyLimit::new(1).queue(1).transform(handler_fn({
+            let gate = gate.clone();
+            move || {
+                let gate = gate.clone();
+                async move {
+                    let _ = gate.acquire().await.unwrap();
+                }
+            }
+        })));
+        let request = || Request::new(Body::empty());
+
+        // 第一个请求正在处理，第二个请求在队列中等待。
+        let tasks = [(); 2].map(|_| {
+            let service = service.clone();
+            tokio::spawn(async move { service.call(request()).await })
+        });
+        for _ in 0..4 {
+            tokio::task::yield_now().await;
+        }
+
+        // 队列已满，第三个请求被立即拒绝。
+        let error = service.call(request()).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&OverloadedError));
+
+        gate.add_permits(2);
+        for task in tasks {
+            assert!(task.await.unwrap().is_ok());
+        }
+    }
+}
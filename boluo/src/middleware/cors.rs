@@ -0,0 +1,595 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use boluo_core::BoxError;
+use boluo_core::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use boluo_core::http::{Method, StatusCode};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::{Request, RequestParts};
+use boluo_core::response::{IntoResponse, Response, TransparentError};
+use boluo_core::service::Service;
+
+/// 跨域资源共享（CORS）中间件。
+///
+/// 预检请求会直接由中间件响应，不会到达内部服务，因此可以放在 [`Router`] 的外层使用。
+///
+/// 内部服务返回的错误会被包装为 [`CorsError`]，服务器将原始错误转换为响应后再添加 CORS 标头，
+/// 浏览器才能读取错误响应。
+///
+/// # 例子
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use boluo::http::{HeaderValue, Method, header};
+/// use boluo::middleware::{AllowOrigin, Cors};
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// let cors = Cors::new()
+///     .allow_origin(AllowOrigin::list([
+///         HeaderValue::from_static("https://example.com"),
+///         HeaderValue::from_static("https://app.example.com"),
+///     ]))
+///     .allow_methods([Method::GET, Method::POST])
+///     .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+///     .allow_credentials(true)
+///     .max_age(Duration::from_secs(600));
+///
+/// let router = Router::new().with(cors);
+/// ```
+///
+/// [`Router`]: crate::route::Router
+#[derive(Debug, Clone)]
+pub struct Cors {
+    allow_origin: AllowOrigin,
+    allow_methods: Option<AllowValue>,
+    allow_headers: Option<AllowValue>,
+    allow_credentials: bool,
+    max_age: Option<HeaderValue>,
+    expose_headers: Option<HeaderValue>,
+    vary: Option<HeaderValue>,
+}
+
+impl Cors {
+    /// 创建不允许任何跨域请求的 [`Cors`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 创建允许任意来源、请求方法和请求标头，并公开所有响应标头的 [`Cors`] 实例。
+    pub fn permissive() -> Self {
+        Self::new()
+            .allow_origin(AllowOrigin::any())
+            .allow_any_method()
+            .allow_any_header()
+            .expose_any_header()
+    }
+
+    /// 设置允许的来源。
+    ///
+    /// 默认不允许任何来源。
+    pub fn allow_origin(mut self, origin: impl Into<AllowOrigin>) -> Self {
+        self.allow_origin = origin.into();
+        self
+    }
+
+    /// 设置允许的请求方法。
+    pub fn allow_methods<I>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = Method>,
+    {
+        self.allow_methods = join_values(methods.into_iter().map(|method| method.to_string()))
+            .map(AllowValue::Const);
+        self
+    }
+
+    /// 允许任意请求方法，预检响应将返回请求的 `Access-Control-Request-Method` 标头的值。
+    pub fn allow_any_method(mut self) -> Self {
+        self.allow_methods = Some(AllowValue::Mirror);
+        self
+    }
+
+    /// 设置允许的请求标头。
+    pub fn allow_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.allow_headers =
+            join_values(headers.into_iter().map(|name| name.to_string())).map(AllowValue::Const);
+        self
+    }
+
+    /// 允许任意请求标头，预检响应将返回请求的 `Access-Control-Request-Headers` 标头的值。
+    pub fn allow_any_header(mut self) -> Self {
+        self.allow_headers = Some(AllowValue::Mirror);
+        self
+    }
+
+    /// 设置是否允许请求携带凭据。
+    ///
+    /// 允许携带凭据时，[`AllowOrigin::any`] 将返回请求的 `Origin` 标头的值而不是 `*`。
+    pub fn allow_credentials(mut self, allow: bool) -> Self {
+        self.allow_credentials = allow;
+        self
+    }
+
+    /// 设置预检请求结果的缓存时间。
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(HeaderValue::from(max_age.as_secs()));
+        self
+    }
+
+    /// 设置允许浏览器访问的响应标头。
+    pub fn expose_headers<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.expose_headers = join_values(headers.into_iter().map(|name| name.to_string()));
+        self
+    }
+
+    /// 允许浏览器访问所有响应标头。
+    ///
+    /// 浏览器不会为携带凭据的请求公开所有响应标头。
+    pub fn expose_any_header(mut self) -> Self {
+        self.expose_headers = Some(HeaderValue::from_static("*"));
+        self
+    }
+
+    /// 设置响应的 `Vary` 标头包含的标头名称。
+    ///
+    /// 默认值为 `Origin`、`Access-Control-Request-Method` 和 `Access-Control-Request-Headers`。
+    pub fn vary<I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.vary = join_values(headers.into_iter().map(|name| name.to_string()));
+        self
+    }
+
+    fn preflight_headers(&self, request: &Request, headers: &mut HeaderMap) {
+        if let Some(methods) = self.allow_methods.as_ref().and_then(|methods| {
+            methods.value(request.headers(), header::ACCESS_CONTROL_REQUEST_METHOD)
+        }) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+        if let Some(allow_headers) = self.allow_headers.as_ref().and_then(|allow_headers| {
+            allow_headers.value(request.headers(), header::ACCESS_CONTROL_REQUEST_HEADERS)
+        }) {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+        if let Some(max_age) = &self.max_age {
+            headers.insert(header::ACCESS_CONTROL_MAX_AGE, max_age.clone());
+        }
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self {
+            allow_origin: AllowOrigin::default(),
+            allow_methods: None,
+            allow_headers: None,
+            allow_credentials: false,
+            max_age: None,
+            expose_headers: None,
+            vary: Some(HeaderValue::from_static(
+                "origin, access-control-request-method, access-control-request-headers",
+            )),
+        }
+    }
+}
+
+impl<S> Middleware<S> for Cors {
+    type Service = CorsService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        CorsService {
+            service,
+            cors: Arc::new(self),
+        }
+    }
+}
+
+/// 中间件 [`Cors`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct CorsService<S> {
+    service: S,
+    cors: Arc<Cors>,
+}
+
+impl<S> Service<Request> for CorsService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let cors = &self.cors;
+        let mut headers = HeaderMap::new();
+
+        let allow_origin = request.headers().get(header::ORIGIN).and_then(|origin| {
+            cors.allow_origin
+                .value(origin, request.parts(), cors.allow_credentials)
+        });
+        let allowed = allow_origin.is_some();
+        if let Some(allow_origin) = allow_origin {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+            if cors.allow_credentials {
+                headers.insert(
+                    header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                    HeaderValue::from_static("true"),
+                );
+            }
+        }
+
+        if is_preflight(&request) {
+            if allowed {
+                cors.preflight_headers(&request, &mut headers);
+            }
+            let mut response = StatusCode::OK.into_response_always();
+            apply_headers(&mut response, headers, cors.vary.as_ref());
+            return Ok(response);
+        }
+
+        if allowed {
+            if let Some(expose_headers) = &cors.expose_headers {
+                headers.insert(
+                    header::ACCESS_CONTROL_EXPOSE_HEADERS,
+                    expose_headers.clone(),
+                );
+            }
+        }
+
+        let result = match self.service.call(request).await {
+            Ok(response) => response.into_response().map_err(Into::into),
+            Err(e) => Err(e.into()),
+        };
+        match result {
+            Ok(mut response) => {
+                apply_headers(&mut response, headers, cors.vary.as_ref());
+                Ok(response)
+            }
+            Err(error) => Err(CorsError {
+                headers,
+                vary: cors.vary.clone(),
+                error,
+            }
+            .into()),
+        }
+    }
+}
+
+/// 携带 CORS 标头的错误。
+///
+/// 中间件 [`Cors`] 会将内部服务返回的错误包装为此类型，错误源是原始错误。
+///
+/// 此类型是 [`TransparentError`]，对应的响应由服务器的 [`ErrorResponder`] 将原始错误转换而来，
+/// 并携带 CORS 标头。
+///
+/// [`ErrorResponder`]: crate::response::ErrorResponder
+pub struct CorsError {
+    headers: HeaderMap,
+    vary: Option<HeaderValue>,
+    error: BoxError,
+}
+
+impl CorsError {
+    /// 获取原始错误的引用。
+    pub fn inner(&self) -> &BoxError {
+        &self.error
+    }
+
+    /// 获取原始错误。
+    pub fn into_inner(self) -> BoxError {
+        self.error
+    }
+}
+
+impl std::fmt::Debug for CorsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CorsError")
+            .field("headers", &self.headers)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Display for CorsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cross-origin request failed: {}", self.error)
+    }
+}
+
+impl std::error::Error for CorsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+impl TransparentError for CorsError {
+    fn map_response(&self, mut response: Response) -> Response {
+        apply_headers(&mut response, self.headers.clone(), self.vary.as_ref());
+        response
+    }
+}
+
+/// 允许跨域请求的来源。
+#[derive(Default, Clone)]
+pub struct AllowOrigin {
+    kind: OriginKind,
+}
+
+type OriginPredicate = Arc<dyn Fn(&HeaderValue, &RequestParts) -> bool + Send + Sync>;
+
+#[derive(Default, Clone)]
+enum OriginKind {
+    #[default]
+    None,
+    Any,
+    List(Vec<HeaderValue>),
+    Predicate(OriginPredicate),
+}
+
+impl AllowOrigin {
+    /// 允许任意来源。
+    pub fn any() -> Self {
+        Self {
+            kind: OriginKind::Any,
+        }
+    }
+
+    /// 仅允许给定的来源。
+    pub fn exact(origin: HeaderValue) -> Self {
+        Self::list([origin])
+    }
+
+    /// 允许列表中的来源。
+    pub fn list<I>(origins: I) -> Self
+    where
+        I: IntoIterator<Item = HeaderValue>,
+    {
+        Self {
+            kind: OriginKind::List(origins.into_iter().collect()),
+        }
+    }
+
+    /// 允许使给定函数返回 `true` 的来源。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo::middleware::AllowOrigin;
+    ///
+    /// let origin = AllowOrigin::predicate(|origin, _parts| {
+    ///     origin.as_bytes().ends_with(b".example.com")
+    /// });
+    /// ```
+    pub fn predicate<F>(f: F) -> Self
+    where
+        F: Fn(&HeaderValue, &RequestParts) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            kind: OriginKind::Predicate(Arc::new(f)),
+        }
+    }
+
+    /// 返回 `Access-Control-Allow-Origin` 标头的值，来源不被允许时返回 `None`。
+    fn value(
+        &self,
+        origin: &HeaderValue,
+        parts: &RequestParts,
+        allow_credentials: bool,
+    ) -> Option<HeaderValue> {
+        match &self.kind {
+            OriginKind::None => None,
+            OriginKind::Any if allow_credentials => Some(origin.clone()),
+            OriginKind::Any => Some(HeaderValue::from_static("*")),
+            OriginKind::List(origins) => origins.contains(origin).then(|| origin.clone()),
+            OriginKind::Predicate(f) => f(origin, parts).then(|| origin.clone()),
+        }
+    }
+}
+
+impl From<HeaderValue> for AllowOrigin {
+    fn from(origin: HeaderValue) -> Self {
+        Self::exact(origin)
+    }
+}
+
+impl<const N: usize> From<[HeaderValue; N]> for AllowOrigin {
+    fn from(origins: [HeaderValue; N]) -> Self {
+        Self::list(origins)
+    }
+}
+
+impl From<Vec<HeaderValue>> for AllowOrigin {
+    fn from(origins: Vec<HeaderValue>) -> Self {
+        Self::list(origins)
+    }
+}
+
+impl std::fmt::Debug for AllowOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            OriginKind::None => f.write_str("None"),
+            OriginKind::Any => f.write_str("Any"),
+            OriginKind::List(origins) => f.debug_tuple("List").field(origins).finish(),
+            OriginKind::Predicate(_) => f.write_str("Predicate"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum AllowValue {
+    Const(HeaderValue),
+    Mirror,
+}
+
+impl AllowValue {
+    fn value(&self, headers: &HeaderMap, request_header: HeaderName) -> Option<HeaderValue> {
+        match self {
+            AllowValue::Const(value) => Some(value.clone()),
+            AllowValue::Mirror => headers.get(request_header).cloned(),
+        }
+    }
+}
+
+fn join_values(values: impl Iterator<Item = String>) -> Option<HeaderValue> {
+    let value = values.collect::<Vec<_>>().join(", ");
+    if value.is_empty() {
+        return None;
+    }
+    Some(HeaderValue::try_from(value).expect("header names and methods are valid header values"))
+}
+
+fn is_preflight(request: &Request) -> bool {
+    request.method() == Method::OPTIONS
+        && request.headers().contains_key(header::ORIGIN)
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+}
+
+fn apply_headers(response: &mut Response, headers: HeaderMap, vary: Option<&HeaderValue>) {
+    response.headers_mut().extend(headers);
+    if let Some(vary) = vary {
+        response.headers_mut().append(header::VARY, vary.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::BoxError;
+    use boluo_core::body::{Body, LengthLimitError};
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::header::{self, HeaderValue};
+    use boluo_core::http::{Method, StatusCode};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::Response;
+    use boluo_core::service::{Service, service_fn};
+
+    use super::{AllowOrigin, Cors, CorsError};
+    use crate::response::error_responder;
+
+    fn request(method: Method, origin: &'static str) -> Request {
+        Request::builder()
+            .method(method)
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "PUT")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "x-token")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn preflight() {
+        let cors = Cors::new()
+            .allow_origin(HeaderValue::from_static("https://a.com"))
+            .allow_methods([Method::GET, Method::PUT])
+            .allow_any_header()
+            .allow_credentials(true);
+        let service = cors.transform(handler_fn(|| async { StatusCode::IM_A_TEAPOT }));
+
+        let response = service
+            .call(request(Method::OPTIONS, "https://a.com"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://a.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, PUT");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_HEADERS], "x-token");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert!(headers.contains_key(header::VARY));
+
+        let response = service
+            .call(request(Method::OPTIONS, "https://b.com"))
+            .await
+            .unwrap();
+        assert!(
+            !response
+                .headers()
+                .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        );
+    }
+
+    #[tokio::test]
+    async fn actual_request() {
+        let cors = Cors::new()
+            .allow_origin(AllowOrigin::predicate(|origin, _| {
+                origin.as_bytes().ends_with(b".a.com")
+            }))
+            .expose_headers([header::ETAG]);
+        let service = cors.transform(handler_fn(|| async { StatusCode::IM_A_TEAPOT }));
+
+        let response = service
+            .call(request(Method::GET, "https://x.a.com"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://x.a.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_EXPOSE_HEADERS], "etag");
+
+        let service = Cors::permissive().transform(handler_fn(|| async {}));
+        let response = service
+            .call(request(Method::GET, "https://b.com"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    }
+
+    #[tokio::test]
+    async fn error_response() {
+        let cors = Cors::new()
+            .allow_origin(HeaderValue::from_static("https://a.com"))
+            .allow_credentials(true);
+
+        let service = cors.clone().transform(service_fn(|_: Request| async {
+            Err::<Response, _>(LengthLimitError::new(4))
+        }));
+        let error = service
+            .call(request(Method::POST, "https://a.com"))
+            .await
+            .unwrap_err();
+        // 错误原样返回给外层，由服务器的 `ErrorResponder` 转换为响应。
+        let error = error.downcast_ref::<CorsError>().unwrap();
+        assert!(error.inner().is::<LengthLimitError>());
+
+        let response = error_responder().respond(error).unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://a.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert!(headers.contains_key(header::VARY));
+
+        let service = cors.transform(service_fn(|_: Request| async {
+            Err::<Response, _>(BoxError::from("unknown"))
+        }));
+        let error = service
+            .call(request(Method::POST, "https://a.com"))
+            .await
+            .unwrap_err();
+        let response = error_responder().respond_always(error.as_ref());
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://a.com"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use boluo_core::BoxError;
+use boluo_core::http::StatusCode;
+use boluo_core::http::header::{self, HeaderValue};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+use boluo_core::service::Service;
+
+use super::encoding::{self, Encoding};
+
+/// 解压请求主体的中间件。
+///
+/// 根据请求的 `Content-Encoding` 标头解压 `gzip`、`br`、`zstd` 或 `deflate` 编码的请求主体，
+/// 使 [`Json`]、[`Form`] 等提取器可以直接读取解压后的数据。
+///
+/// 请求使用不支持的编码时返回 [`UnsupportedEncodingError`]，解压后的数据长度超过
+/// [`Decompression::limit`] 时读取主体会返回 [`LengthLimitError`]。请求主体已经被外层的
+/// [`BodyLimit`] 限制时，解压后的数据使用两者中较小的限制。内层的 [`BodyLimit`]
+/// 只能进一步收紧解压后的数据长度，无法放宽 [`Decompression::limit`]。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::middleware::Decompression;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// let router = Router::new().with(Decompression::new().limit(8 * 1024 * 1024));
+/// ```
+///
+/// [`Json`]: crate::extract::Json
+/// [`Form`]: crate::extract::Form
+/// [`LengthLimitError`]: boluo_core::body::LengthLimitError
+/// [`BodyLimit`]: crate::middleware::BodyLimit
+#[derive(Debug, Clone, Copy)]
+pub struct Decompression {
+    gzip: bool,
+    deflate: bool,
+    br: bool,
+    zstd: bool,
+    limit: usize,
+}
+
+impl Decompression {
+    /// 创建支持所有编码的 [`Decompression`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置是否支持 `gzip` 编码。
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.gzip = enable;
+        self
+    }
+
+    /// 设置是否支持 `deflate` 编码。
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.deflate = enable;
+        self
+    }
+
+    /// 设置是否支持 `br` 编码。
+    pub fn br(mut self, enable: bool) -> Self {
+        self.br = enable;
+        self
+    }
+
+    /// 设置是否支持 `zstd` 编码。
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.zstd = enable;
+        self
+    }
+
+    /// 设置解压后的数据允许的最大字节数。
+    ///
+    /// 默认值为 16 MiB。
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    fn is_enabled(&self, encoding: Encoding) -> bool {
+        match encoding {
+            Encoding::Gzip => self.gzip,
+            Encoding::Deflate => self.deflate,
+            Encoding::Br => self.br,
+            Encoding::Zstd => self.zstd,
+        }
+    }
+
+    fn accept_encoding(&self) -> HeaderValue {
+        let value = [
+            Encoding::Gzip,
+            Encoding::Deflate,
+            Encoding::Br,
+            Encoding::Zstd,
+        ]
+        .into_iter()
+        .filter(|encoding| self.is_enabled(*encoding))
+        .map(Encoding::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+        HeaderValue::try_from(value).expect("encoding names are valid header values")
+    }
+
+    /// 解析 `Content-Encoding` 标头，按照编码的应用顺序返回编码列表。
+    fn encodings(&self, request: &Request) -> Result<Vec<Encoding>, UnsupportedEncodingError> {
+        let mut encodings = Vec::new();
+        for value in request.headers().get_all(header::CONTENT_ENCODING) {
+            let value = value
+                .to_str()
+                .map_err(|_| self.unsupported(String::from_utf8_lossy(value.as_bytes())))?;
+            for name in value.split(',').map(str::trim) {
+                if name.is_empty() || name.eq_ignore_ascii_case("identity") {
+                    continue;
+                }
+                match Encoding::parse(name).filter(|encoding| self.is_enabled(*encoding)) {
+                    Some(encoding) => encodings.push(encoding),
+                    None => return Err(self.unsupported(name)),
+                }
+            }
+        }
+        Ok(encodings)
+    }
+
+    fn unsupported(&self, encoding: impl Into<String>) -> UnsupportedEncodingError {
+        UnsupportedEncodingError {
+            encoding: encoding.into(),
+            accept_encoding: self.accept_encoding(),
+        }
+    }
+}
+
+impl Default for Decompression {
+    fn default() -> Self {
+        Self {
+            gzip: true,
+            deflate: true,
+            br: true,
+            zstd: true,
+            limit: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl<S> Middleware<S> for Decompression {
+    type Service = DecompressionService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        DecompressionService {
+            service,
+            decompression: self,
+        }
+    }
+}
+
+/// 中间件 [`Decompression`] 返回的服务。
+#[derive(Debug, Clone, Copy)]
+pub struct DecompressionService<S> {
+    service: S,
+    decompression: Decompression,
+}
+
+impl<S> Service<Request> for DecompressionService<S>
+where
+    S: Service<Request>,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+
+    async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
+        let encodings = self.decompression.encodings(&request)?;
+        if !encodings.is_empty() {
+            let mut body = std::mem::take(request.body_mut());
+            let limit = body
+                .length_limit()
+                .map_or(self.decompression.limit, |limit| {
+                    limit.min(self.decompression.limit)
+                });
+            for encoding in encodings.into_iter().rev() {
+                body = encoding::decode(body, encoding, limit);
+            }
+            *request.body_mut() = body;
+
+            let headers = request.headers_mut();
+            headers.remove(header::CONTENT_ENCODING);
+            headers.remove(header::CONTENT_LENGTH);
+        }
+
+        self.service.call(request).await.map_err(Into::into)
+    }
+}
+
+/// 请求主体使用了不支持的编码。
+#[derive(Debug, Clone)]
+pub struct UnsupportedEncodingError {
+    encoding: String,
+    accept_encoding: HeaderValue,
+}
+
+impl UnsupportedEncodingError {
+    /// 获取不支持的编码名称。
+    pub fn encoding(&self) -> &str {
+        &self.encoding
+    }
+}
+
+impl std::fmt::Display for UnsupportedEncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unsupported content encoding `{}`", self.encoding)
+    }
+}
+
+impl std::error::Error for UnsupportedEncodingError {}
+
+impl ErrorResponse for UnsupportedEncodingError {
+    fn status(&self) -> StatusCode {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    }
+
+    fn to_response(&self) -> Response {
+        let mut response = self.status().into_response_always();
+        response
+            .headers_mut()
+            .insert(header::ACCEPT_ENCODING, self.accept_encoding.clone());
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use async_compression::tokio::write::GzipEncoder;
+    use boluo_core::body::{Body, LengthLimitError};
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::header;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+    use tokio::io::AsyncWriteExt;
+
+    use super::{Decompression, UnsupportedEncodingError};
+    use crate::extract::Json;
+    use crate::middleware::BodyLimit;
+
+    async fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzipEncoder::new(Vec::new());
+        encoder.write_all(data).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        encoder.into_inner()
+    }
+
+    fn request(encoding: &'static str, body: Vec<u8>) -> Request {
+        Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_ENCODING, encoding)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn decompress_json() {
+        let service =
+            Decompression::new().transform(handler_fn(|Json(value): Json<Vec<u32>>| async move {
+                value.len().to_string()
+            }));
+
+        let body = gzip(b"[1, 2, 3]").await;
+        assert!(service.call(request("gzip", body)).await.is_ok());
+
+        let error = service
+            .call(request("compress", Vec::new()))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error
+                .downcast_ref::<UnsupportedEncodingError>()
+                .unwrap()
+                .encoding(),
+            "compress"
+        );
+    }
+
+    #[tokio::test]
+    async fn limit_decompressed_size() {
+        let service =
+            Decompression::new()
+                .limit(1024)
+                .transform(handler_fn(|body: Body| async move {
+                    body.to_bytes().await.map(|bytes| bytes.len().to_string())
+                }));
+
+        let body = gzip(&[0; 4096]).await;
+        assert!(body.len() < 1024);
+        let error = service.call(request("gzip", body)).await.unwrap_err();
+        assert!(error.is::<LengthLimitError>());
+    }
+
+    #[tokio::test]
+    async fn inner_body_limit() {
+        let handler = handler_fn(|body: Body| async move {
+            body.to_bytes().await.map(|bytes| bytes.len().to_string())
+        });
+        let service = Decompression::new()
+            .limit(1024)
+            .transform(BodyLimit::new(1 << 30).transform(handler));
+
+        // 内层更宽松的限制不会解除解压后的数据长度限制。
+        let body = gzip(&[0; 4096]).await;
+        let error = service.call(request("gzip", body)).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<LengthLimitError>().unwrap().limit(),
+            1024
+        );
+
+        let handler = handler_fn(|body: Body| async move {
+            body.to_bytes().await.map(|bytes| bytes.len().to_string())
+        });
+        let service = Decompression::new().transform(BodyLimit::new(1024).transform(handler));
+
+        // 内层更严格的限制仍然生效。
+        let body = gzip(&[0; 4096]).await;
+        let error = service.call(request("gzip", body)).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<LengthLimitError>().unwrap().limit(),
+            1024
+        );
+    }
+
+    #[tokio::test]
+    async fn outer_body_limit() {
+        let handler = handler_fn(|body: Body| async move {
+            body.to_bytes().await.map(|bytes| bytes.len().to_string())
+        });
+        let service = BodyLimit::new(1024).transform(Decompression::new().transform(handler));
+
+        let body = gzip(&[0; 1024]).await;
+        assert!(service.call(request("gzip", body)).await.is_ok());
+
+        // 压缩后的数据没有超过外层的限制，解压后的数据同样受外层的限制。
+        let body = gzip(&[0; 4096]).await;
+        assert!(body.len() < 1024);
+        let error = service.call(request("gzip", body)).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<LengthLimitError>().unwrap().limit(),
+            1024
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::io;
+
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
+use async_compression::tokio::write::{BrotliEncoder, GzipEncoder, ZlibEncoder, ZstdEncoder};
+use boluo_core::BoxError;
+use boluo_core::body::{Body, BodyDataStream, Bytes, LengthLimitError};
+use futures_util::stream::Peekable;
+use futures_util::{FutureExt, StreamExt, TryStreamExt};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::io::{ReaderStream, StreamReader};
+
+/// 内容编码。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Encoding {
+    Gzip,
+    Deflate,
+    Br,
+    Zstd,
+}
+
+impl Encoding {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Br => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    pub(super) fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("gzip") || value.eq_ignore_ascii_case("x-gzip") {
+            Some(Encoding::Gzip)
+        } else if value.eq_ignore_ascii_case("deflate") {
+            Some(Encoding::Deflate)
+        } else if value.eq_ignore_ascii_case("br") {
+            Some(Encoding::Br)
+        } else if value.eq_ignore_ascii_case("zstd") {
+            Some(Encoding::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+/// 写入数据并将结果保存在内部缓冲区中的编码器。
+///
+/// HTTP 的 `deflate` 编码是 zlib 格式包装的 DEFLATE 数据（RFC 9110 §8.4.1.2），而不是原始的 DEFLATE 数据。
+enum Coder {
+    Gzip(GzipEncoder<Vec<u8>>),
+    Deflate(ZlibEncoder<Vec<u8>>),
+    Br(Box<BrotliEncoder<Vec<u8>>>),
+    Zstd(ZstdEncoder<Vec<u8>>),
+}
+
+macro_rules! coder_dispatch {
+    ($coder:expr, $inner:ident => $expr:expr) => {
+        match $coder {
+            Coder::Gzip($inner) => $expr,
+            Coder::Deflate($inner) => $expr,
+            Coder::Br($inner) => $expr,
+            Coder::Zstd($inner) => $expr,
+        }
+    };
+}
+
+impl Coder {
+    fn encoder(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => Coder::Gzip(GzipEncoder::new(Vec::new())),
+            Encoding::Deflate => Coder::Deflate(ZlibEncoder::new(Vec::new())),
+            Encoding::Br => Coder::Br(Box::new(BrotliEncoder::new(Vec::new()))),
+            Encoding::Zstd => Coder::Zstd(ZstdEncoder::new(Vec::new())),
+        }
+    }
+
+    fn writer(&mut self) -> &mut (dyn AsyncWrite + Unpin + Send) {
+        coder_dispatch!(self, inner => inner)
+    }
+
+    fn take(&mut self) -> Bytes {
+        coder_dispatch!(self, inner => Bytes::from(std::mem::take(inner.get_mut())))
+    }
+
+    /// 写入一块数据，并取出已经产生的输出。
+    async fn write(&mut self, data: &[u8]) -> io::Result<Bytes> {
+        self.writer().write_all(data).await?;
+        Ok(self.take())
+    }
+
+    /// 刷新编码器，并取出已经产生的输出。
+    async fn flush(&mut self) -> io::Result<Bytes> {
+        self.writer().flush().await?;
+        Ok(self.take())
+    }
+
+    /// 结束写入，并取出剩余的输出。
+    async fn finish(&mut self) -> io::Result<Bytes> {
+        self.writer().shutdown().await?;
+        Ok(self.take())
+    }
+}
+
+struct Transcode {
+    stream: Peekable<BodyDataStream>,
+    coder: Coder,
+    done: bool,
+}
+
+impl Transcode {
+    async fn next(&mut self) -> Option<io::Result<Bytes>> {
+        while !self.done {
+            let output = match self.stream.next().await {
+                Some(Ok(data)) => match self.coder.write(&data).await {
+                    // 下一块数据还没有准备好时刷新编码器，保证流式响应的数据能够及时发送，
+                    // 连续到达的数据则一起压缩，不影响压缩率。
+                    Ok(output) if self.is_pending() => self
+                        .coder
+                        .flush()
+                        .await
+                        .map(|rest| [output, rest].concat().into()),
+                    output => output,
+                },
+                Some(Err(e)) => Err(io::Error::other(e)),
+                None => {
+                    self.done = true;
+                    self.coder.finish().await
+                }
+            };
+            match output {
+                Ok(output) if output.is_empty() => {}
+                Ok(output) => return Some(Ok(output)),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+
+    /// 判断主体的下一块数据是否需要等待。
+    fn is_pending(&mut self) -> bool {
+        std::pin::Pin::new(&mut self.stream)
+            .peek()
+            .now_or_never()
+            .is_none()
+    }
+}
+
+/// 使用给定的编码压缩主体。
+///
+/// 主体的下一块数据需要等待时会刷新编码器，保证流式响应的数据能够及时发送。
+pub(super) fn encode(body: Body, encoding: Encoding) -> Body {
+    let transcode = Transcode {
+        stream: body.into_data_stream().peekable(),
+        coder: Coder::encoder(encoding),
+        done: false,
+    };
+    Body::from_data_stream(futures_util::stream::unfold(
+        transcode,
+        |mut transcode| async move { transcode.next().await.map(|item| (item, transcode)) },
+    ))
+}
+
+/// 使用给定的编码解压主体，解压后的数据长度超过 `limit` 时返回 [`LengthLimitError`]。
+///
+/// 长度限制在解压的数据流中检查，内层的 [`Body::limit`] 无法放宽这个限制。
+pub(super) fn decode(body: Body, encoding: Encoding, limit: usize) -> Body {
+    let reader = StreamReader::new(body.into_data_stream().map_err(io::Error::other));
+    match encoding {
+        Encoding::Gzip => {
+            let mut decoder = GzipDecoder::new(reader);
+            decoder.multiple_members(true);
+            read_body(decoder, limit)
+        }
+        Encoding::Deflate => read_body(ZlibDecoder::new(reader), limit),
+        Encoding::Br => read_body(BrotliDecoder::new(reader), limit),
+        Encoding::Zstd => read_body(ZstdDecoder::new(reader), limit),
+    }
+}
+
+fn read_body<R>(reader: R, limit: usize) -> Body
+where
+    R: AsyncRead + Send + 'static,
+{
+    let mut read = 0usize;
+    let stream = ReaderStream::new(reader)
+        .map_err(from_io_error)
+        .and_then(move |data| {
+            read = read.saturating_add(data.len());
+            futures_util::future::ready(if read > limit {
+                Err(LengthLimitError::new(limit).into())
+            } else {
+                Ok(data)
+            })
+        });
+    Body::from_data_stream(stream).limit(limit)
+}
+
+/// 取出 [`io::Error`] 包装的原始错误，保留主体返回的错误类型。
+fn from_io_error(error: io::Error) -> BoxError {
+    if error.get_ref().is_some() {
+        error.into_inner().unwrap()
+    } else {
+        error.into()
+    }
+}
@@ -0,0 +1,133 @@
+use boluo_core::BoxError;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response};
+use boluo_core::service::Service;
+
+use super::{ConcurrencyLimit, ConcurrencyLimitService};
+
+/// 服务过载时拒绝请求的中间件。
+///
+/// 正在处理的请求数达到上限时，新请求会立即被拒绝并返回 [`OverloadedError`]，服务器将其转换为
+/// `503 SERVICE_UNAVAILABLE` 响应。与 [`ConcurrencyLimit`] 不同，被拒绝的请求不会等待，
+/// 相当于等待队列长度为零的 [`ConcurrencyLimit`]。
+///
+/// 每次调用 [`Middleware::transform`] 都会创建独立的计数器，通过 [`Router::with_for_each`]
+/// 应用时每个路由独立计数。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::handler::handler_fn;
+/// use boluo::middleware::LoadShed;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// let router = Router::new()
+///     .route("/", handler_fn(|| async { "hello" }))
+///     .with(LoadShed::new(1024));
+/// ```
+///
+/// [`OverloadedError`]: super::OverloadedError
+/// [`Router::with_for_each`]: crate::route::Router::with_for_each
+#[derive(Debug, Clone, Copy)]
+pub struct LoadShed {
+    limit: ConcurrencyLimit,
+}
+
+impl LoadShed {
+    /// 创建最多同时处理 `max` 个请求的 [`LoadShed`]。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `max` 为零或超过 [`Semaphore::MAX_PERMITS`] 时会触发 panic。
+    ///
+    /// [`Semaphore::MAX_PERMITS`]: tokio::sync::Semaphore::MAX_PERMITS
+    pub fn new(max: usize) -> Self {
+        Self {
+            limit: ConcurrencyLimit::new(max).queue(0),
+        }
+    }
+}
+
+impl<S> Middleware<S> for LoadShed {
+    type Service = LoadShedService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        LoadShedService {
+            service: self.limit.transform(service),
+        }
+    }
+}
+
+/// 中间件 [`LoadShed`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct LoadShedService<S> {
+    service: ConcurrencyLimitService<S>,
+}
+
+impl<S> Service<Request> for LoadShedService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        self.service.call(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+    use tokio::sync::Semaphore;
+
+    use super::LoadShed;
+    use crate::middleware::OverloadedError;
+
+    #[test]
+    #[should_panic]
+    fn zero() {
+        LoadShed::new(0);
+    }
+
+    #[tokio::test]
+    async fn shed() {
+        let gate = Arc::new(Semaphore::new(0));
+        let service = Arc::new(LoadShed::new(1).transform(handler_fn({
+            let gate = gate.clone();
+            move || {
+                let gate = gate.clone();
+                async move {
+                    let _ = gate.acquire().await.unwrap();
+                }
+            }
+        })));
+        let request = || Request::new(Body::empty());
+
+        let task = tokio::spawn({
+            let service = service.clone();
+            async move { service.call(request()).await }
+        });
+        for _ in 0..4 {
+            tokio::task::yield_now().await;
+        }
+
+        // 正在处理的请求数达到上限，新请求被立即拒绝。
+        let error = service.call(request()).await.unwrap_err();
+        assert_eq!(error.downcast_ref(), Some(&OverloadedError));
+
+        gate.add_permits(2);
+        assert!(task.await.unwrap().is_ok());
+        assert!(service.call(request()).await.is_ok());
+    }
+}
@@ -2,6 +2,48 @@
 
 pub use boluo_core::middleware::*;
 
+mod body_limit;
+mod catch_panic;
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(feature = "tokio")]
+mod concurrency_limit;
+mod cors;
+#[cfg(feature = "compression")]
+mod decompression;
+#[cfg(feature = "compression")]
+mod encoding;
 mod extension;
+#[cfg(feature = "tokio")]
+mod load_shed;
+mod rate_limit;
+#[cfg(feature = "request-id")]
+mod request_id;
+#[cfg(feature = "tokio")]
+mod timeout;
+#[cfg(feature = "tracing")]
+mod trace;
 
+pub(crate) use body_limit::take_limited_body;
+pub use body_limit::{BodyLimit, BodyLimitService};
+pub use catch_panic::{CatchPanic, CatchPanicService, PanicError};
+#[cfg(feature = "compression")]
+pub use compression::{Compression, CompressionService};
+#[cfg(feature = "tokio")]
+pub use concurrency_limit::{ConcurrencyLimit, ConcurrencyLimitService, OverloadedError};
+pub use cors::{AllowOrigin, Cors, CorsError, CorsService};
+#[cfg(feature = "compression")]
+pub use decompression::{Decompression, DecompressionService, UnsupportedEncodingError};
 pub use extension::{Extension, ExtensionService};
+#[cfg(feature = "tokio")]
+pub use load_shed::{LoadShed, LoadShedService};
+pub use rate_limit::{
+    MemoryRateLimitStore, Quota, RateLimit, RateLimitAlgorithm, RateLimitDecision, RateLimitError,
+    RateLimitKey, RateLimitService, RateLimitStore,
+};
+#[cfg(feature = "request-id")]
+pub use request_id::{RequestId, RequestIdError, RequestIdService, RequestIdValue};
+#[cfg(feature = "tokio")]
+pub use timeout::{Timeout, TimeoutService};
+#[cfg(feature = "tracing")]
+pub use trace::{Trace, TraceService};
@@ -0,0 +1,671 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use boluo_core::BoxError;
+use boluo_core::http::StatusCode;
+use boluo_core::http::header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+use boluo_core::service::Service;
+
+use crate::listener::ConnectInfo;
+use crate::route::MatchedPath;
+
+const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+const RATELIMIT_POLICY: HeaderName = HeaderName::from_static("ratelimit-policy");
+
+/// 内存存储每处理多少次请求清理一次过期的状态。
+const CLEANUP_INTERVAL: u64 = 1024;
+
+/// 限流配额，表示每个周期内允许的请求数。
+///
+/// 配额同时决定了允许的突发请求数，空闲足够长的时间后，客户端可以在短时间内连续发送 `limit` 个请求。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    limit: u32,
+    period: Duration,
+}
+
+impl Quota {
+    /// 创建每 `period` 允许 `limit` 个请求的配额。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `limit` 或 `period` 为零，或者 `period` 除以 `limit` 不足 1 纳秒时会触发 panic。
+    pub fn new(limit: u32, period: Duration) -> Self {
+        assert!(limit > 0, "rate limit must be greater than zero");
+        assert!(
+            !(period / limit).is_zero(),
+            "rate limit period must be at least one nanosecond per request"
+        );
+        Self { limit, period }
+    }
+
+    /// 创建每秒允许 `limit` 个请求的配额。
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    /// 创建每分钟允许 `limit` 个请求的配额。
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    /// 创建每小时允许 `limit` 个请求的配额。
+    pub fn per_hour(limit: u32) -> Self {
+        Self::new(limit, Duration::from_secs(60 * 60))
+    }
+
+    /// 获取每个周期内允许的请求数。
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// 获取周期。
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// 获取恢复一个请求配额所需的时间。
+    pub fn interval(&self) -> Duration {
+        self.period / self.limit
+    }
+}
+
+/// 限流算法。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitAlgorithm {
+    /// 令牌桶算法。桶的容量为配额的请求数，令牌按固定速率补充，每个请求消耗一个令牌。
+    #[default]
+    TokenBucket,
+    /// 通用信元速率算法（GCRA），一种滑动窗口算法。只记录理论到达时间，请求均匀分布在窗口内。
+    Gcra,
+}
+
+/// 限流存储对请求做出的决定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    /// 是否允许请求。
+    pub allowed: bool,
+    /// 周期内允许的请求数。
+    pub limit: u32,
+    /// 剩余可用的请求数。
+    pub remaining: u32,
+    /// 配额完全恢复所需的时间。
+    pub reset: Duration,
+    /// 请求被拒绝时，到下一次允许请求所需的时间。
+    pub retry_after: Duration,
+}
+
+/// 保存限流状态的存储。
+///
+/// 存储负责执行限流算法并保存每个键的状态，可以实现此特征将状态保存在多个服务实例共享的后端中。
+pub trait RateLimitStore: Send + Sync {
+    /// 为键消耗一个请求配额，并返回限流决定。
+    fn acquire(
+        &self,
+        key: &str,
+        quota: Quota,
+        algorithm: RateLimitAlgorithm,
+    ) -> impl Future<Output = Result<RateLimitDecision, BoxError>> + Send;
+}
+
+/// 将限流状态保存在内存中的存储，克隆的实例共享同一份数据。
+///
+/// 配额已经完全恢复的状态会被定期清理，每个状态按照更新它时使用的配额判断是否恢复，
+/// 因此多个使用不同配额的 [`RateLimit`] 可以共享同一个存储。
+#[derive(Debug, Default, Clone)]
+pub struct MemoryRateLimitStore {
+    inner: Arc<MemoryInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryInner {
+    states: Mutex<HashMap<String, Entry>>,
+    requests: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    state: RateLimitState,
+    /// 配额完全恢复的时刻，之后状态可以被清理。
+    full_at: Instant,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RateLimitState {
+    TokenBucket { tokens: f64, updated_at: Instant },
+    Gcra { tat: Instant },
+}
+
+impl RateLimitState {
+    /// 获取配额完全恢复的时刻。
+    fn full_at(&self, quota: Quota) -> Instant {
+        match *self {
+            RateLimitState::TokenBucket { tokens, updated_at } => {
+                let missing = f64::from(quota.limit) - tokens;
+                updated_at + quota.interval().mul_f64(missing.max(0.0))
+            }
+            RateLimitState::Gcra { tat } => tat,
+        }
+    }
+}
+
+impl MemoryRateLimitStore {
+    /// 创建空的 [`MemoryRateLimitStore`]。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn acquire_at(
+        &self,
+        key: &str,
+        quota: Quota,
+        algorithm: RateLimitAlgorithm,
+        now: Instant,
+    ) -> RateLimitDecision {
+        let mut states = self.inner.states.lock().unwrap_or_else(|e| e.into_inner());
+
+        if self.inner.requests.fetch_add(1, Ordering::Relaxed) % CLEANUP_INTERVAL == 0 {
+            states.retain(|_, entry| entry.full_at > now);
+        }
+
+        let state = states.get(key).map(|entry| entry.state);
+        let (state, decision) = match algorithm {
+            RateLimitAlgorithm::TokenBucket => token_bucket(state, quota, now),
+            RateLimitAlgorithm::Gcra => gcra(state, quota, now),
+        };
+        if let Some(state) = state {
+            let full_at = state.full_at(quota);
+            states.insert(key.to_owned(), Entry { state, full_at });
+        }
+        decision
+    }
+}
+
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn acquire(
+        &self,
+        key: &str,
+        quota: Quota,
+        algorithm: RateLimitAlgorithm,
+    ) -> Result<RateLimitDecision, BoxError> {
+        Ok(self.acquire_at(key, quota, algorithm, Instant::now()))
+    }
+}
+
+fn token_bucket(
+    state: Option<RateLimitState>,
+    quota: Quota,
+    now: Instant,
+) -> (Option<RateLimitState>, RateLimitDecision) {
+    let limit = f64::from(quota.limit);
+    let interval = quota.interval();
+    let tokens = match state {
+        Some(RateLimitState::TokenBucket { tokens, updated_at }) => {
+            let refilled =
+                now.saturating_duration_since(updated_at).as_secs_f64() / interval.as_secs_f64();
+            (tokens + refilled).min(limit)
+        }
+        _ => limit,
+    };
+
+    if tokens < 1.0 {
+        let decision = RateLimitDecision {
+            allowed: false,
+            limit: quota.limit,
+            remaining: 0,
+            reset: interval.mul_f64(limit - tokens),
+            retry_after: interval.mul_f64(1.0 - tokens),
+        };
+        return (None, decision);
+    }
+
+    let tokens = tokens - 1.0;
+    let state = RateLimitState::TokenBucket {
+        tokens,
+        updated_at: now,
+    };
+    let decision = RateLimitDecision {
+        allowed: true,
+        limit: quota.limit,
+        remaining: tokens as u32,
+        reset: interval.mul_f64(limit - tokens),
+        retry_after: Duration::ZERO,
+    };
+    (Some(state), decision)
+}
+
+fn gcra(
+    state: Option<RateLimitState>,
+    quota: Quota,
+    now: Instant,
+) -> (Option<RateLimitState>, RateLimitDecision) {
+    let interval = quota.interval();
+    let tat = match state {
+        Some(RateLimitState::Gcra { tat }) => tat.max(now),
+        _ => now,
+    };
+    let new_tat = tat + interval;
+
+    // 理论到达时间超过当前时间一个周期以上，说明窗口内的请求已经用完。
+    let ahead = new_tat - now;
+    if ahead > quota.period {
+        let decision = RateLimitDecision {
+            allowed: false,
+            limit: quota.limit,
+            remaining: 0,
+            reset: tat - now,
+            retry_after: ahead - quota.period,
+        };
+        return (None, decision);
+    }
+
+    let remaining = ((quota.period - ahead).as_nanos() / interval.as_nanos()) as u32;
+    let decision = RateLimitDecision {
+        allowed: true,
+        limit: quota.limit,
+        remaining,
+        reset: ahead,
+        retry_after: Duration::ZERO,
+    };
+    (Some(RateLimitState::Gcra { tat: new_tat }), decision)
+}
+
+type KeyFn = dyn Fn(&Request) -> Option<String> + Send + Sync;
+
+/// 从请求中获取限流键的方式。
+///
+/// 拥有相同键的请求共享同一份配额，无法获取键的请求不受限流。
+#[derive(Clone)]
+pub struct RateLimitKey(Arc<KeyFn>);
+
+impl RateLimitKey {
+    /// 使用连接的远程 IP 地址作为键，需要服务器在请求扩展中插入 [`ConnectInfo`]。
+    pub fn remote_ip() -> Self {
+        Self::from_fn(|request| {
+            request
+                .extensions()
+                .get::<ConnectInfo>()
+                .map(|info| info.remote.ip().to_string())
+        })
+    }
+
+    /// 使用请求标头的值作为键，例如反向代理设置的 `x-real-ip` 标头或 API 密钥标头。
+    pub fn header(name: HeaderName) -> Self {
+        Self::from_fn(move |request| {
+            request
+                .headers()
+                .get(&name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+        })
+    }
+
+    /// 使用请求扩展中的认证主体作为键，例如中间件 [`RequireAuth`] 保存的认证主体。
+    ///
+    /// [`RequireAuth`]: crate::auth::RequireAuth
+    pub fn principal<P, F>(f: F) -> Self
+    where
+        P: Send + Sync + 'static,
+        F: Fn(&P) -> String + Send + Sync + 'static,
+    {
+        Self::from_fn(move |request| request.extensions().get::<P>().map(&f))
+    }
+
+    /// 使用路由器匹配的路由路径作为键，同一路由的所有请求共享配额。
+    pub fn matched_path() -> Self {
+        Self::from_fn(|request| {
+            request
+                .extensions()
+                .get::<MatchedPath>()
+                .map(|path| path.as_str().to_owned())
+        })
+    }
+
+    /// 使用自定义函数获取键。
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(&Request) -> Option<String> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+}
+
+impl std::fmt::Debug for RateLimitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RateLimitKey").finish_non_exhaustive()
+    }
+}
+
+/// 限制请求速率的中间件。
+///
+/// 中间件使用 [`RateLimitKey`] 获取请求的键，并通过 [`RateLimitStore`] 为键消耗配额，默认使用连接的远程 IP
+/// 地址作为键、令牌桶算法和内存存储。请求超过配额时返回 [`RateLimitError`]，服务器将其转换为携带
+/// `Retry-After` 标头的 `429 TOO_MANY_REQUESTS` 响应。所有响应都会携带 `RateLimit-Limit`、
+/// `RateLimit-Remaining`、`RateLimit-Reset` 和 `RateLimit-Policy` 标头。
+///
+/// 中间件在路由器内部应用时（例如通过 [`Router::with_for_each`] 或 [`MethodRoute::with`]），
+/// 限流状态的键会包含匹配的路由路径，每个路由独立计算配额。在路由器外部应用时，所有路由共享配额。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::handler::handler_fn;
+/// use boluo::middleware::{Quota, RateLimit, RateLimitAlgorithm};
+/// use boluo::route::{Router, post};
+/// use boluo::service::ServiceExt;
+///
+/// let login = post(handler_fn(|| async { "ok" })).with(
+///     RateLimit::new(Quota::per_minute(5)).algorithm(RateLimitAlgorithm::Gcra),
+/// );
+///
+/// let router = Router::new()
+///     .route("/login", login)
+///     .route("/", handler_fn(|| async { "hello" }))
+///     .with_for_each(RateLimit::new(Quota::per_second(100)));
+/// ```
+///
+/// [`Router::with_for_each`]: crate::route::Router::with_for_each
+/// [`MethodRoute::with`]: crate::route::MethodRoute::with
+#[derive(Debug, Clone)]
+pub struct RateLimit<T = MemoryRateLimitStore> {
+    quota: Quota,
+    algorithm: RateLimitAlgorithm,
+    key: RateLimitKey,
+    store: T,
+}
+
+impl RateLimit {
+    /// 使用配额创建 [`RateLimit`]。
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            algorithm: RateLimitAlgorithm::default(),
+            key: RateLimitKey::remote_ip(),
+            store: MemoryRateLimitStore::new(),
+        }
+    }
+}
+
+impl<T> RateLimit<T> {
+    /// 设置限流算法，默认为令牌桶算法。
+    pub fn algorithm(mut self, algorithm: RateLimitAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// 设置获取限流键的方式，默认使用连接的远程 IP 地址。
+    pub fn key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// 设置保存限流状态的存储。
+    pub fn store<U: RateLimitStore>(self, store: U) -> RateLimit<U> {
+        RateLimit {
+            quota: self.quota,
+            algorithm: self.algorithm,
+            key: self.key,
+            store,
+        }
+    }
+}
+
+impl<T, S> Middleware<S> for RateLimit<T> {
+    type Service = RateLimitService<T, S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        RateLimitService {
+            service,
+            rate_limit: self,
+        }
+    }
+}
+
+/// 中间件 [`RateLimit`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct RateLimitService<T, S> {
+    service: S,
+    rate_limit: RateLimit<T>,
+}
+
+impl<T, S> Service<Request> for RateLimitService<T, S>
+where
+    T: RateLimitStore,
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let Some(mut key) = (self.rate_limit.key.0)(&request) else {
+            return self
+                .service
+                .call(request)
+                .await
+                .map_err(Into::into)?
+                .into_response()
+                .map_err(Into::into);
+        };
+        if let Some(path) = request.extensions().get::<MatchedPath>() {
+            key = format!("{path}\n{key}");
+        }
+
+        let decision = self
+            .rate_limit
+            .store
+            .acquire(&key, self.rate_limit.quota, self.rate_limit.algorithm)
+            .await?;
+        if !decision.allowed {
+            return Err(RateLimitError {
+                decision,
+                quota: self.rate_limit.quota,
+            }
+            .into());
+        }
+
+        let mut response = self
+            .service
+            .call(request)
+            .await
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)?;
+        set_headers(self.rate_limit.quota, &decision, response.headers_mut());
+        Ok(response)
+    }
+}
+
+/// 请求超过限流配额的错误。
+///
+/// 错误对应的响应为 `429 TOO_MANY_REQUESTS`，并携带 `Retry-After` 和 `RateLimit-*` 标头。
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitError {
+    decision: RateLimitDecision,
+    quota: Quota,
+}
+
+impl RateLimitError {
+    /// 获取限流决定。
+    pub fn decision(&self) -> &RateLimitDecision {
+        &self.decision
+    }
+
+    /// 获取到下一次允许请求所需的时间。
+    pub fn retry_after(&self) -> Duration {
+        self.decision.retry_after
+    }
+}
+
+impl std::fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rate limit exceeded, retry after {}s",
+            ceil_secs(self.decision.retry_after)
+        )
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
+impl ErrorResponse for RateLimitError {
+    fn status(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn to_response(&self) -> Response {
+        let mut response = self.status().into_response_always();
+        let headers = response.headers_mut();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from(ceil_secs(self.decision.retry_after)),
+        );
+        set_headers(self.quota, &self.decision, headers);
+        response
+    }
+}
+
+fn set_headers(quota: Quota, decision: &RateLimitDecision, headers: &mut HeaderMap) {
+    let policy = format!("{};w={}", quota.limit, ceil_secs(quota.period));
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(
+        RATELIMIT_RESET,
+        HeaderValue::from(ceil_secs(decision.reset)),
+    );
+    headers.insert(
+        RATELIMIT_POLICY,
+        HeaderValue::try_from(policy).expect("policy is a valid header value"),
+    );
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::StatusCode;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+
+    use super::{
+        MemoryRateLimitStore, Quota, RateLimit, RateLimitAlgorithm, RateLimitError, RateLimitKey,
+    };
+    use crate::response::error_responder;
+    use crate::route::Router;
+
+    #[test]
+    fn algorithms() {
+        let quota = Quota::new(3, Duration::from_secs(3));
+        for algorithm in [RateLimitAlgorithm::TokenBucket, RateLimitAlgorithm::Gcra] {
+            let store = MemoryRateLimitStore::new();
+            let now = Instant::now();
+
+            // 允许突发 3 个请求，之后每秒恢复 1 个请求。
+            for remaining in [2, 1, 0] {
+                let decision = store.acquire_at("a", quota, algorithm, now);
+                assert!(decision.allowed, "{algorithm:?}");
+                assert_eq!(decision.remaining, remaining, "{algorithm:?}");
+            }
+            let decision = store.acquire_at("a", quota, algorithm, now);
+            assert!(!decision.allowed, "{algorithm:?}");
+            assert_eq!(
+                decision.retry_after,
+                Duration::from_secs(1),
+                "{algorithm:?}"
+            );
+
+            // 不同的键互不影响。
+            assert!(store.acquire_at("b", quota, algorithm, now).allowed);
+
+            let later = now + Duration::from_secs(1);
+            assert!(store.acquire_at("a", quota, algorithm, later).allowed);
+            assert!(!store.acquire_at("a", quota, algorithm, later).allowed);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_interval() {
+        Quota::new(2, Duration::from_nanos(1));
+    }
+
+    #[test]
+    fn shared_store_cleanup() {
+        let store = MemoryRateLimitStore::new();
+        let algorithm = RateLimitAlgorithm::TokenBucket;
+        let now = Instant::now();
+        assert!(
+            store
+                .acquire_at("hour", Quota::per_hour(1), algorithm, now)
+                .allowed
+        );
+
+        // 使用较短配额的请求触发清理时，不会清理使用较长配额且尚未恢复的状态。
+        let later = now + Duration::from_secs(2);
+        for _ in 0..super::CLEANUP_INTERVAL {
+            store.acquire_at("second", Quota::per_second(1), algorithm, later);
+        }
+        assert!(
+            !store
+                .acquire_at("hour", Quota::per_hour(1), algorithm, later)
+                .allowed
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limit_service() {
+        let rate_limit = RateLimit::new(Quota::new(2, Duration::from_secs(10)))
+            .key(RateLimitKey::header("x-api-key".parse().unwrap()));
+        let router = Router::new()
+            .route("/a", handler_fn(|| async { "a" }))
+            .route("/b", handler_fn(|| async { "b" }))
+            .with_for_each(rate_limit);
+        let request = |path: &str| {
+            Request::builder()
+                .uri(path)
+                .header("x-api-key", "key")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        for remaining in ["1", "0"] {
+            let response = router.call(request("/a")).await.unwrap();
+            let headers = response.headers();
+            assert_eq!(headers["ratelimit-limit"], "2");
+            assert_eq!(headers["ratelimit-remaining"], remaining);
+            assert_eq!(headers["ratelimit-policy"], "2;w=10");
+        }
+
+        let error = router.call(request("/a")).await.unwrap_err();
+        assert!(error.is::<RateLimitError>());
+        let response = error_responder().respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let headers = response.headers();
+        assert_eq!(headers["retry-after"], "5");
+        assert_eq!(headers["ratelimit-remaining"], "0");
+        assert_eq!(headers["ratelimit-reset"], "10");
+
+        // 键包含匹配的路由路径，每个路由独立计算配额。
+        let response = router.call(request("/b")).await.unwrap();
+        assert_eq!(response.headers()["ratelimit-remaining"], "1");
+
+        // 无法获取键的请求不受限流。
+        let response = router.call(Request::builder().uri("/a").body(Body::empty()).unwrap());
+        assert!(response.await.is_ok());
+    }
+}
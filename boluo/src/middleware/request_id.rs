@@ -0,0 +1,419 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use boluo_core::BoxError;
+use boluo_core::extract::{FromRequest, OptionalFromRequest};
+use boluo_core::http::header::{HeaderName, HeaderValue};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response, TransparentError};
+use boluo_core::service::Service;
+
+use crate::extract::ExtensionError;
+
+/// 默认的请求标识符标头。
+const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 请求标识符的最大长度，超过此长度的请求标识符会被忽略。
+const MAX_LEN: usize = 256;
+
+type Generator = Arc<dyn Fn() -> HeaderValue + Send + Sync>;
+
+/// 为请求设置标识符的中间件。
+///
+/// 中间件读取请求的 `x-request-id` 标头作为请求标识符，标头不存在或无效时生成一个 UUID v4。
+/// 请求标识符会以 [`RequestIdValue`] 的形式保存在请求扩展中，同时写入请求和响应的标头。
+///
+/// 内部服务返回的错误会被包装为 [`RequestIdError`]，使记录错误时可以获取请求标识符。
+/// [`RequestIdError`] 的错误源是原始错误，它对应的响应由服务器的 [`ErrorResponder`]
+/// 将原始错误转换而来，并同样携带请求标识符标头。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::BoxError;
+/// use boluo::handler::handler_fn;
+/// use boluo::middleware::{RequestId, RequestIdError, RequestIdValue, filter_fn};
+/// use boluo::request::Request;
+/// use boluo::route::Router;
+/// use boluo::service::{Service, ServiceExt};
+///
+/// // 日志中间件，需要在 `RequestId` 之后应用才能获取请求标识符。
+/// async fn log<S>(request: Request, service: &S) -> Result<S::Response, S::Error>
+/// where
+///     S: Service<Request, Error = BoxError>,
+/// {
+///     let result = service.call(request).await;
+///     if let Err(error) = &result {
+///         match error.downcast_ref::<RequestIdError>() {
+///             Some(e) => println!("[{}] {}", e.id(), e.inner()),
+///             None => println!("{error}"),
+///         }
+///     }
+///     result
+/// }
+///
+/// let router = Router::new()
+///     .route("/", handler_fn(|id: RequestIdValue| async move { id.to_string() }))
+///     .with(RequestId::new())
+///     .with(filter_fn(log));
+/// ```
+///
+/// [`ErrorResponder`]: crate::response::ErrorResponder
+#[derive(Clone)]
+pub struct RequestId {
+    header: HeaderName,
+    generator: Generator,
+}
+
+impl RequestId {
+    /// 创建读取 `x-request-id` 标头的 [`RequestId`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置保存请求标识符的标头。
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// 设置生成请求标识符的函数，默认生成 UUID v4。
+    ///
+    /// 生成的标识符必须由可见 ASCII 字符组成，且长度不超过 256 字节，否则使用 UUID v4 代替。
+    pub fn generator<F>(mut self, f: F) -> Self
+    where
+        F: Fn() -> HeaderValue + Send + Sync + 'static,
+    {
+        self.generator = Arc::new(f);
+        self
+    }
+
+    fn request_id(&self, request: &Request) -> RequestIdValue {
+        let value = request
+            .headers()
+            .get(&self.header)
+            .filter(|value| is_valid(value))
+            .cloned()
+            .unwrap_or_else(|| {
+                Some((self.generator)())
+                    .filter(is_valid)
+                    .unwrap_or_else(generate)
+            });
+        RequestIdValue(value)
+    }
+}
+
+impl Default for RequestId {
+    fn default() -> Self {
+        Self {
+            header: X_REQUEST_ID,
+            generator: Arc::new(generate),
+        }
+    }
+}
+
+impl std::fmt::Debug for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestId")
+            .field("header", &self.header)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Middleware<S> for RequestId {
+    type Service = RequestIdService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        RequestIdService {
+            service,
+            request_id: self,
+        }
+    }
+}
+
+/// 中间件 [`RequestId`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    service: S,
+    request_id: RequestId,
+}
+
+impl<S> Service<Request> for RequestIdService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
+        let id = self.request_id.request_id(&request);
+        let header = &self.request_id.header;
+        request.headers_mut().insert(header.clone(), id.0.clone());
+        request.extensions_mut().insert(id.clone());
+
+        let result = self
+            .service
+            .call(request)
+            .await
+            .map_err(Into::into)
+            .and_then(|response| response.into_response().map_err(Into::into));
+
+        match result {
+            Ok(mut response) => {
+                if !response.headers().contains_key(header) {
+                    response.headers_mut().insert(header.clone(), id.0);
+                }
+                Ok(response)
+            }
+            Err(error) => Err(RequestIdError {
+                id,
+                header: header.clone(),
+                error,
+            }
+            .into()),
+        }
+    }
+}
+
+/// 请求标识符。
+///
+/// 由中间件 [`RequestId`] 保存在请求扩展中，可以作为提取器使用。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestIdValue(HeaderValue);
+
+impl RequestIdValue {
+    /// 获取请求标识符的字符串形式。
+    pub fn as_str(&self) -> &str {
+        self.0
+            .to_str()
+            .expect("request id is always a visible ASCII string")
+    }
+
+    /// 获取请求标识符的标头值。
+    pub fn header_value(&self) -> &HeaderValue {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RequestIdValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromRequest for RequestIdValue {
+    type Error = ExtensionError;
+
+    async fn from_request(request: &mut Request) -> Result<Self, Self::Error> {
+        let opt = Option::<RequestIdValue>::from_request(request)
+            .await
+            .map_err(|e| match e {})?;
+        opt.ok_or_else(|| ExtensionError::MissingExtension {
+            name: std::any::type_name::<RequestIdValue>(),
+        })
+    }
+}
+
+impl OptionalFromRequest for RequestIdValue {
+    type Error = Infallible;
+
+    async fn from_request(request: &mut Request) -> Result<Option<Self>, Self::Error> {
+        Ok(request.extensions().get::<RequestIdValue>().cloned())
+    }
+}
+
+/// 携带请求标识符的错误。
+///
+/// 中间件 [`RequestId`] 会将内部服务返回的错误包装为此类型，错误源是原始错误。
+///
+/// 此类型是 [`TransparentError`]，对应的响应由服务器的 [`ErrorResponder`] 将原始错误转换而来，
+/// 并携带请求标识符标头。
+///
+/// [`ErrorResponder`]: crate::response::ErrorResponder
+pub struct RequestIdError {
+    id: RequestIdValue,
+    header: HeaderName,
+    error: BoxError,
+}
+
+impl RequestIdError {
+    /// 获取请求标识符。
+    pub fn id(&self) -> &RequestIdValue {
+        &self.id
+    }
+
+    /// 获取原始错误的引用。
+    pub fn inner(&self) -> &BoxError {
+        &self.error
+    }
+
+    /// 获取原始错误。
+    pub fn into_inner(self) -> BoxError {
+        self.error
+    }
+}
+
+impl std::fmt::Debug for RequestIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestIdError")
+            .field("id", &self.id)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for RequestIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "request `{}` failed: {}", self.id, self.error)
+    }
+}
+
+impl std::error::Error for RequestIdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+impl TransparentError for RequestIdError {
+    fn map_response(&self, mut response: Response) -> Response {
+        if !response.headers().contains_key(&self.header) {
+            response
+                .headers_mut()
+                .insert(self.header.clone(), self.id.0.clone());
+        }
+        response
+    }
+}
+
+/// 判断请求携带的标识符是否可用，拒绝空值、过长的值和非可见 ASCII 字符。
+fn is_valid(value: &HeaderValue) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_LEN
+        && value.as_bytes().iter().all(|b| b.is_ascii_graphic())
+}
+
+fn generate() -> HeaderValue {
+    let mut buf = uuid::Uuid::encode_buffer();
+    let id = uuid::Uuid::new_v4().hyphenated().encode_lower(&mut buf);
+    HeaderValue::from_str(id).expect("uuid is a valid header value")
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::StatusCode;
+    use boluo_core::http::header::{HeaderName, HeaderValue};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::ErrorResponse;
+    use boluo_core::service::Service;
+
+    use super::{RequestId, RequestIdError, RequestIdValue};
+    use crate::extract::JsonError;
+    use crate::response::error_responder;
+
+    #[tokio::test]
+    async fn propagate_and_generate() {
+        let service =
+            RequestId::new().transform(handler_fn(
+                |id: RequestIdValue| async move { id.to_string() },
+            ));
+
+        let request = Request::builder()
+            .header("x-request-id", "abc-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.headers()["x-request-id"], "abc-123");
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "abc-123");
+
+        let request = Request::builder()
+            .header("x-request-id", "")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        let id = response.headers()["x-request-id"].clone();
+        assert_eq!(id.len(), 36);
+        assert_eq!(
+            response.into_body().to_bytes().await.unwrap(),
+            id.as_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_header_and_error() {
+        let service = RequestId::new()
+            .header(HeaderName::from_static("x-trace-id"))
+            .generator(|| HeaderValue::from_static("generated"))
+            .transform(handler_fn(|| async {
+                Err::<(), _>(JsonError::UnsupportedContentType)
+            }));
+
+        let error = service.call(Request::default()).await.unwrap_err();
+        let error = error.downcast_ref::<RequestIdError>().unwrap();
+        assert_eq!(error.id().as_str(), "generated");
+        assert!(error.inner().is::<JsonError>());
+
+        let response = error_responder().respond(error).unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(response.headers()["x-trace-id"], "generated");
+    }
+
+    #[tokio::test]
+    async fn server_error_responder() {
+        #[derive(Debug)]
+        struct Conflict;
+
+        impl std::fmt::Display for Conflict {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("conflict")
+            }
+        }
+
+        impl std::error::Error for Conflict {}
+
+        impl ErrorResponse for Conflict {
+            fn status(&self) -> StatusCode {
+                StatusCode::CONFLICT
+            }
+        }
+
+        let service = RequestId::new()
+            .generator(|| HeaderValue::from_static("generated"))
+            .transform(handler_fn(|| async { Err::<(), _>(Conflict) }));
+        let error = service.call(Request::default()).await.unwrap_err();
+
+        // 只在服务器的 `ErrorResponder` 中注册的错误类型。
+        let responder = error_responder().register::<Conflict>();
+        assert_eq!(responder.status(error.as_ref()), Some(StatusCode::CONFLICT));
+        let response = responder.respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(response.headers()["x-request-id"], "generated");
+
+        // 没有注册的错误类型使用 500 响应，同样携带请求标识符标头。
+        let response = error_responder().respond_always(error.as_ref());
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()["x-request-id"], "generated");
+    }
+
+    #[tokio::test]
+    async fn invalid_generated_id() {
+        let service = RequestId::new()
+            .generator(|| HeaderValue::from_static("not valid"))
+            .transform(handler_fn(
+                |id: RequestIdValue| async move { id.to_string() },
+            ));
+
+        let response = service.call(Request::default()).await.unwrap();
+        let id = response.headers()["x-request-id"].clone();
+        assert_eq!(id.len(), 36);
+        assert_eq!(
+            response.into_body().to_bytes().await.unwrap(),
+            id.as_bytes()
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use boluo_core::BoxError;
+use boluo_core::body::{Body, Bytes, Frame, HttpBody, SizeHint};
+use boluo_core::http::StatusCode;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response};
+use boluo_core::service::{Service, TimeoutError};
+use tokio::time::Sleep;
+
+/// 限制请求处理时间的中间件。
+///
+/// - [`Timeout::total`] 限制服务返回响应的时间，超时返回 [`TimeoutError`]。
+/// - [`Timeout::first_byte`] 限制服务返回响应后，响应主体产生第一块数据的时间，超时后读取主体会返回
+///   [`TimeoutError`]，适用于流式响应。
+///
+/// # 例子
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use boluo::http::StatusCode;
+/// use boluo::middleware::Timeout;
+/// use boluo::route::Router;
+/// use boluo::service::ServiceExt;
+///
+/// let timeout = Timeout::new()
+///     .total(Duration::from_secs(30))
+///     .first_byte(Duration::from_secs(10))
+///     .status(StatusCode::REQUEST_TIMEOUT);
+///
+/// let router = Router::new().with(timeout);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    total: Option<Duration>,
+    first_byte: Option<Duration>,
+    status: StatusCode,
+}
+
+impl Timeout {
+    /// 创建不限制处理时间的 [`Timeout`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置服务返回响应的最长时间。
+    pub fn total(mut self, duration: Duration) -> Self {
+        self.total = Some(duration);
+        self
+    }
+
+    /// 设置服务返回响应后，响应主体产生第一块数据的最长时间。
+    pub fn first_byte(mut self, duration: Duration) -> Self {
+        self.first_byte = Some(duration);
+        self
+    }
+
+    /// 设置超时错误对应的状态码。
+    ///
+    /// 默认值为 `503 Service Unavailable`。
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    fn error(&self, duration: Duration) -> TimeoutError {
+        TimeoutError::new(duration).with_status(self.status)
+    }
+}
+
+impl Default for Timeout {
+    fn default() -> Self {
+        Self {
+            total: None,
+            first_byte: None,
+            status: StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+impl<S> Middleware<S> for Timeout {
+    type Service = TimeoutService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        TimeoutService {
+            service,
+            timeout: self,
+        }
+    }
+}
+
+/// 中间件 [`Timeout`] 返回的服务。
+#[derive(Debug, Clone, Copy)]
+pub struct TimeoutService<S> {
+    service: S,
+    timeout: Timeout,
+}
+
+impl<S> Service<Request> for TimeoutService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let fut = self.service.call(request);
+        let result = match self.timeout.total {
+            Some(duration) => tokio::time::timeout(duration, fut)
+                .await
+                .map_err(|_| self.timeout.error(duration))?,
+            None => fut.await,
+        };
+        let response = result
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)?;
+
+        Ok(match self.timeout.first_byte {
+            Some(duration) => {
+                let error = self.timeout.error(duration);
+                response.map(|body| Body::new(FirstByteTimeout::new(body, duration, error)))
+            }
+            None => response,
+        })
+    }
+}
+
+pin_project_lite::pin_project! {
+    struct FirstByteTimeout {
+        inner: Body,
+        #[pin]
+        sleep: Option<Sleep>,
+        error: TimeoutError,
+    }
+}
+
+impl FirstByteTimeout {
+    fn new(inner: Body, duration: Duration, error: TimeoutError) -> Self {
+        Self {
+            inner,
+            sleep: Some(tokio::time::sleep(duration)),
+            error,
+        }
+    }
+}
+
+impl HttpBody for FirstByteTimeout {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let poll = Pin::new(this.inner).poll_frame(cx);
+        if this.sleep.is_none() {
+            return poll;
+        }
+        match poll {
+            Poll::Pending => {
+                let elapsed = this
+                    .sleep
+                    .as_mut()
+                    .as_pin_mut()
+                    .is_some_and(|sleep| sleep.poll(cx).is_ready());
+                if elapsed {
+                    this.sleep.set(None);
+                    return Poll::Ready(Some(Err((*this.error).into())));
+                }
+                Poll::Pending
+            }
+            Poll::Ready(Some(Ok(frame))) if !frame.is_data() => Poll::Ready(Some(Ok(frame))),
+            poll => {
+                this.sleep.set(None);
+                poll
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::Response;
+    use boluo_core::service::{Service, TimeoutError};
+
+    use super::Timeout;
+
+    #[tokio::test(start_paused = true)]
+    async fn total() {
+        let service = Timeout::new()
+            .total(Duration::from_secs(5))
+            .transform(handler_fn(|| async {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }));
+        let error = service.call(Request::default()).await.unwrap_err();
+        assert!(error.is::<TimeoutError>());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn first_byte() {
+        let service = Timeout::new()
+            .first_byte(Duration::from_secs(5))
+            .transform(handler_fn(|request: Request| async move {
+                let delay = request.uri().path().len() as u64;
+                let stream = futures_util::stream::once(async move {
+                    tokio::time::sleep(Duration::from_secs(delay)).await;
+                    Ok::<_, std::convert::Infallible>("data")
+                });
+                Response::new(Body::from_data_stream(stream))
+            }));
+
+        let request = Request::builder().uri("/").body(Body::empty()).unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "data");
+
+        let request = Request::builder()
+            .uri("/slow-response")
+            .body(Body::empty())
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        let error = response.into_body().to_bytes().await.unwrap_err();
+        assert!(error.is::<TimeoutError>());
+    }
+}
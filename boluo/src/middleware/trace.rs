@@ -0,0 +1,392 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use boluo_core::BoxError;
+use boluo_core::http::StatusCode;
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponder, IntoResponse, Response};
+use boluo_core::service::Service;
+use tracing::field::{Empty, debug, display};
+use tracing::{Instrument, Level, Span};
+
+use crate::listener::ConnectInfo;
+use crate::route::MatchedPath;
+
+type MakeSpan = Arc<dyn Fn(&Request) -> Span + Send + Sync>;
+type OnRequest = Arc<dyn Fn(&Request, &Span) + Send + Sync>;
+type OnResponse = Arc<dyn Fn(&Response, Duration, &Span) + Send + Sync>;
+type OnError = Arc<dyn Fn(&BoxError, StatusCode, Duration, &Span) + Send + Sync>;
+
+/// 根据运行时的日志级别创建 span。
+macro_rules! dyn_span {
+    ($level:expr, $($args:tt)*) => {
+        match $level {
+            Level::ERROR => tracing::span!(Level::ERROR, $($args)*),
+            Level::WARN => tracing::span!(Level::WARN, $($args)*),
+            Level::INFO => tracing::span!(Level::INFO, $($args)*),
+            Level::DEBUG => tracing::span!(Level::DEBUG, $($args)*),
+            Level::TRACE => tracing::span!(Level::TRACE, $($args)*),
+        }
+    };
+}
+
+/// 根据运行时的日志级别记录事件。
+macro_rules! dyn_event {
+    ($level:expr, $($args:tt)*) => {
+        match $level {
+            Level::ERROR => tracing::event!(Level::ERROR, $($args)*),
+            Level::WARN => tracing::event!(Level::WARN, $($args)*),
+            Level::INFO => tracing::event!(Level::INFO, $($args)*),
+            Level::DEBUG => tracing::event!(Level::DEBUG, $($args)*),
+            Level::TRACE => tracing::event!(Level::TRACE, $($args)*),
+        }
+    };
+}
+
+/// 使用 [`tracing`] 记录请求的中间件。
+///
+/// 默认情况下，中间件为每个请求创建一个名为 `request` 的 span，包含以下字段：
+///
+/// - `method`：请求方法。
+/// - `route`：路由器匹配的路由路径，详情查看 [`MatchedPath`]。
+/// - `path`：请求路径。
+/// - `version`：HTTP 版本。
+/// - `remote_addr`：客户端地址，详情查看 [`ConnectInfo`]。
+/// - `status`：响应状态码，服务返回后记录。
+/// - `latency`：服务返回响应或错误的耗时，不包括发送响应主体的时间。
+///
+/// 服务返回错误时，中间件使用服务器添加到请求扩展中的 [`ErrorResponder`] 确定错误对应的状态码，
+/// 并记录完整的错误链。
+/// 状态码为 `5xx` 时使用 `ERROR` 级别记录事件。
+///
+/// 路由器在匹配成功后才会插入 [`MatchedPath`]，如果需要记录 `route` 字段，应使用
+/// [`Router::with_for_each`] 为路由器内的服务分别应用此中间件。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::handler::handler_fn;
+/// use boluo::middleware::Trace;
+/// use boluo::route::Router;
+/// use tracing::Level;
+///
+/// let router = Router::new()
+///     .route("/users/{id}", handler_fn(|| async { "user" }))
+///     .with_for_each(Trace::new().level(Level::DEBUG));
+/// ```
+///
+/// [`Router::with_for_each`]: crate::route::Router::with_for_each
+#[derive(Clone)]
+pub struct Trace {
+    level: Level,
+    make_span: Option<MakeSpan>,
+    on_request: Option<OnRequest>,
+    on_response: Option<OnResponse>,
+    on_error: Option<OnError>,
+}
+
+impl Trace {
+    /// 创建使用 `INFO` 级别的 [`Trace`] 实例。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 设置默认的 span 和事件使用的日志级别。
+    ///
+    /// 默认值为 `INFO`。
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// 设置为请求创建 span 的函数。
+    ///
+    /// 自定义的 span 需要声明 `status` 和 `latency` 字段，默认的回调函数才能记录这些字段。
+    pub fn make_span<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request) -> Span + Send + Sync + 'static,
+    {
+        self.make_span = Some(Arc::new(f));
+        self
+    }
+
+    /// 设置收到请求时调用的函数。
+    pub fn on_request<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Request, &Span) + Send + Sync + 'static,
+    {
+        self.on_request = Some(Arc::new(f));
+        self
+    }
+
+    /// 设置服务返回响应时调用的函数。
+    pub fn on_response<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Response, Duration, &Span) + Send + Sync + 'static,
+    {
+        self.on_response = Some(Arc::new(f));
+        self
+    }
+
+    /// 设置服务返回错误时调用的函数，参数中的状态码由 [`ErrorResponder`] 确定。
+    pub fn on_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&BoxError, StatusCode, Duration, &Span) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Arc::new(f));
+        self
+    }
+
+    fn span(&self, request: &Request) -> Span {
+        if let Some(make_span) = &self.make_span {
+            return make_span(request);
+        }
+
+        let span = dyn_span!(
+            self.level,
+            "request",
+            method = %request.method(),
+            route = Empty,
+            path = %request.uri().path(),
+            version = ?request.version(),
+            remote_addr = Empty,
+            status = Empty,
+            latency = Empty,
+        );
+        if let Some(matched) = request.extensions().get::<MatchedPath>() {
+            span.record("route", matched.as_str());
+        }
+        if let Some(info) = request.extensions().get::<ConnectInfo>() {
+            span.record("remote_addr", display(info.remote));
+        }
+        span
+    }
+
+    fn request(&self, request: &Request, span: &Span) {
+        match &self.on_request {
+            Some(on_request) => on_request(request, span),
+            None => dyn_event!(self.level, "started processing request"),
+        }
+    }
+
+    fn response(&self, response: &Response, latency: Duration, span: &Span) {
+        if let Some(on_response) = &self.on_response {
+            return on_response(response, latency, span);
+        }
+
+        let status = response.status().as_u16();
+        span.record("status", status);
+        span.record("latency", debug(latency));
+        dyn_event!(
+            self.level,
+            status,
+            latency = ?latency,
+            "finished processing request"
+        );
+    }
+
+    fn error(&self, error: &BoxError, responder: &ErrorResponder, latency: Duration, span: &Span) {
+        let status = responder
+            .status(error.as_ref())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        if let Some(on_error) = &self.on_error {
+            return on_error(error, status, latency, span);
+        }
+
+        let error = display(ErrorChain(error.as_ref()));
+        span.record("status", status.as_u16());
+        span.record("latency", debug(latency));
+        let level = if status.is_server_error() {
+            Level::ERROR
+        } else {
+            self.level
+        };
+        dyn_event!(
+            level,
+            status = status.as_u16(),
+            latency = ?latency,
+            error = error,
+            "failed to process request"
+        );
+    }
+}
+
+impl Default for Trace {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO,
+            make_span: None,
+            on_request: None,
+            on_response: None,
+            on_error: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Trace")
+            .field("level", &self.level)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Middleware<S> for Trace {
+    type Service = TraceService<S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        TraceService {
+            service,
+            trace: Arc::new(self),
+        }
+    }
+}
+
+/// 中间件 [`Trace`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct TraceService<S> {
+    service: S,
+    trace: Arc<Trace>,
+}
+
+impl<S> Service<Request> for TraceService<S>
+where
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
+        let span = self.trace.span(&request);
+        let responder = crate::response::request_error_responder(&request);
+        let start = Instant::now();
+
+        let result = async {
+            self.trace.request(&request, &span);
+            self.service
+                .call(request)
+                .await
+                .map_err(Into::into)?
+                .into_response()
+                .map_err(Into::into)
+        }
+        .instrument(span.clone())
+        .await;
+
+        let latency = start.elapsed();
+        span.in_scope(|| match &result {
+            Ok(response) => self.trace.response(response, latency, &span),
+            Err(error) => self.trace.error(error, &responder, latency, &span),
+        });
+        result
+    }
+}
+
+/// 使用 `: ` 连接错误链中的所有错误。
+struct ErrorChain<'a>(&'a (dyn std::error::Error + 'static));
+
+impl std::fmt::Display for ErrorChain<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut next = self.0.source();
+        while let Some(error) = next {
+            write!(f, ": {error}")?;
+            next = error.source();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::StatusCode;
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::{ErrorResponder, ErrorResponse, Response};
+    use boluo_core::service::{Service, ServiceExt};
+
+    use super::Trace;
+    use crate::extract::JsonError;
+    use crate::route::Router;
+
+    #[tokio::test]
+    async fn hooks() {
+        let statuses = Arc::new(Mutex::new(Vec::new()));
+        let trace = Trace::new()
+            .on_response({
+                let statuses = statuses.clone();
+                move |response, _, _| statuses.lock().unwrap().push(response.status())
+            })
+            .on_error({
+                let statuses = statuses.clone();
+                move |_, status, _, _| statuses.lock().unwrap().push(status)
+            });
+
+        let router = Router::new()
+            .route("/ok", handler_fn(|| async {}))
+            .route(
+                "/error",
+                handler_fn(|| async { Err::<(), _>(JsonError::UnsupportedContentType) }),
+            )
+            .with(trace);
+
+        for path in ["/ok", "/error", "/missing"] {
+            let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+            let _ = router.call(request).await;
+        }
+        assert_eq!(
+            *statuses.lock().unwrap(),
+            [
+                StatusCode::OK,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                StatusCode::NOT_FOUND
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn status_without_response() {
+        #[derive(Debug)]
+        struct Conflict;
+
+        impl std::fmt::Display for Conflict {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("conflict")
+            }
+        }
+
+        impl std::error::Error for Conflict {}
+
+        impl ErrorResponse for Conflict {
+            fn status(&self) -> StatusCode {
+                StatusCode::CONFLICT
+            }
+
+            fn to_response(&self) -> Response {
+                panic!("the response should not be built")
+            }
+        }
+
+        let status = Arc::new(Mutex::new(None));
+        let service = Trace::new()
+            .on_error({
+                let status = status.clone();
+                move |_, s, _, _| *status.lock().unwrap() = Some(s)
+            })
+            .transform(handler_fn(|| async { Err::<(), _>(Conflict) }));
+
+        let mut request = Request::default();
+        request
+            .extensions_mut()
+            .insert(ErrorResponder::new().register::<Conflict>());
+        let _ = service.call(request).await;
+        assert_eq!(*status.lock().unwrap(), Some(StatusCode::CONFLICT));
+    }
+}
@@ -3,13 +3,16 @@
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use boluo_core::body::Bytes;
+use boluo_core::body::{Bytes, LengthLimitError};
 use boluo_core::extract::FromRequest;
-use boluo_core::http::HeaderMap;
 use boluo_core::http::header::CONTENT_TYPE;
+use boluo_core::http::{HeaderMap, StatusCode};
 use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
 use futures_util::Stream;
 
+use crate::middleware::take_limited_body;
+
 /// 解析 `multipart/form-data` 请求的提取器。
 ///
 /// # 例子
@@ -73,7 +76,7 @@ impl FromRequest for Multipart {
             return Err(MultipartError::UnsupportedContentType);
         };
 
-        let stream = std::mem::take(request.body_mut()).into_data_stream();
+        let stream = take_limited_body(request).into_data_stream();
 
         Ok(Self {
             inner: multer::Multipart::new(stream, boundary),
@@ -145,12 +148,19 @@ impl Stream for Field {
 pub enum MultipartError {
     /// 不支持的内容类型。
     UnsupportedContentType,
+    /// 主体长度超过限制。
+    PayloadTooLarge(LengthLimitError),
     /// 解析错误。
     ParseError(String),
 }
 
 impl MultipartError {
     fn from_multer(error: multer::Error) -> Self {
+        if let multer::Error::StreamReadFailed(e) = &error {
+            if let Some(e) = e.downcast_ref::<LengthLimitError>() {
+                return MultipartError::PayloadTooLarge(*e);
+            }
+        }
         MultipartError::ParseError(error.to_string())
     }
 }
@@ -159,6 +169,7 @@ impl std::fmt::Display for MultipartError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             MultipartError::UnsupportedContentType => f.write_str("unsupported content type"),
+            MultipartError::PayloadTooLarge(e) => write!(f, "{e}"),
             MultipartError::ParseError(e) => {
                 write!(f, "failed to parse `multipart/form-data` request ({e})")
             }
@@ -167,3 +178,13 @@ impl std::fmt::Display for MultipartError {
 }
 
 impl std::error::Error for MultipartError {}
+
+impl ErrorResponse for MultipartError {
+    fn status(&self) -> StatusCode {
+        match self {
+            MultipartError::UnsupportedContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            MultipartError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            MultipartError::ParseError(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
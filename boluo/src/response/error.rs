@@ -0,0 +1,89 @@
+use boluo_core::response::ErrorResponder;
+
+/// 创建注册了框架内置错误类型的 [`ErrorResponder`]。
+///
+/// 服务器默认使用此函数返回的 [`ErrorResponder`] 将错误转换为响应，可以在此基础上
+/// 继续注册自定义的错误类型。服务器会将使用的 [`ErrorResponder`] 添加到请求扩展中，
+/// 需要将错误转换为响应或状态码的中间件从请求扩展中获取。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::http::StatusCode;
+/// use boluo::response::{ErrorResponse, error_responder};
+///
+/// #[derive(Debug)]
+/// struct MyError;
+///
+/// impl std::fmt::Display for MyError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "some error message")
+///     }
+/// }
+///
+/// impl std::error::Error for MyError {}
+///
+/// impl ErrorResponse for MyError {
+///     fn status(&self) -> StatusCode {
+///         StatusCode::CONFLICT
+///     }
+/// }
+///
+/// let responder = error_responder().register::<MyError>();
+/// ```
+pub fn error_responder() -> ErrorResponder {
+    let responder = ErrorResponder::new()
+        .register::<boluo_core::body::LengthLimitError>()
+        .register::<crate::route::RouteError>()
+        .register::<crate::extract::JsonError>()
+        .register::<crate::extract::FormError>()
+        .register::<crate::extract::QueryError>()
+        .register::<crate::extract::PathError>()
+        .register::<crate::extract::TypedHeaderError>()
+        .register::<crate::extract::ExtensionError>()
+        .register::<crate::auth::AuthError>()
+        .register::<crate::middleware::RateLimitError>()
+        .register::<crate::middleware::PanicError>();
+
+    #[cfg(feature = "multipart")]
+    let responder = responder.register::<crate::multipart::MultipartError>();
+
+    #[cfg(feature = "ws")]
+    let responder = responder.register::<crate::ws::WebSocketUpgradeError>();
+
+    #[cfg(feature = "tokio")]
+    let responder = responder
+        .register::<boluo_core::service::TimeoutError>()
+        .register::<crate::middleware::OverloadedError>();
+
+    #[cfg(feature = "compression")]
+    let responder = responder.register::<crate::middleware::UnsupportedEncodingError>();
+
+    #[cfg(feature = "static-file")]
+    let responder = responder.register::<crate::static_file::ServeFileError>();
+
+    let responder = responder.register_transparent::<crate::middleware::CorsError>();
+
+    #[cfg(feature = "request-id")]
+    let responder = responder.register_transparent::<crate::middleware::RequestIdError>();
+
+    #[cfg(feature = "csrf")]
+    let responder = responder.register::<crate::csrf::CsrfError>();
+
+    #[cfg(any(feature = "http1", feature = "http2"))]
+    let responder = responder.register::<crate::server::BodyRateError>();
+
+    responder
+}
+
+/// 获取服务器添加到请求扩展中的 [`ErrorResponder`]，不存在时使用 [`error_responder`] 创建的实例。
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+pub(crate) fn request_error_responder(request: &boluo_core::request::Request) -> ErrorResponder {
+    static DEFAULT: std::sync::LazyLock<ErrorResponder> = std::sync::LazyLock::new(error_responder);
+
+    request
+        .extensions()
+        .get::<ErrorResponder>()
+        .unwrap_or(&DEFAULT)
+        .clone()
+}
@@ -0,0 +1,17 @@
+use boluo_core::body::{Body, HttpBody};
+use boluo_core::http::{HeaderValue, StatusCode, header};
+use boluo_core::response::Response;
+
+/// 丢弃 `HEAD` 请求的响应主体，并保留主体长度对应的 `Content-Length` 标头。
+pub(crate) fn strip_body(mut response: Response) -> Response {
+    let status = response.status();
+    let has_content = !(status.is_informational() || status == StatusCode::NO_CONTENT);
+    if has_content && !response.headers().contains_key(header::CONTENT_LENGTH) {
+        if let Some(len) = response.body_mut().size_hint().exact() {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+        }
+    }
+    response.map(|_| Body::empty())
+}
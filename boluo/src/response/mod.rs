@@ -5,14 +5,22 @@ pub use boluo_core::response::*;
 #[cfg(feature = "sse")]
 pub mod sse;
 
+mod error;
 mod extension;
 mod form;
+#[cfg(any(feature = "http1", feature = "http2", feature = "test-util"))]
+mod head;
 mod html;
 mod json;
 mod redirect;
 
+pub use error::error_responder;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+pub(crate) use error::request_error_responder;
 pub use extension::Extension;
 pub use form::{Form, FormResponseError};
+#[cfg(any(feature = "http1", feature = "http2", feature = "test-util"))]
+pub(crate) use head::strip_body;
 pub use html::Html;
 pub use json::{Json, JsonResponseError};
 pub use redirect::{Redirect, RedirectUriError};
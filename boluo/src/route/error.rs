@@ -1,5 +1,8 @@
-use boluo_core::http::StatusCode;
+use boluo_core::http::{Method, StatusCode, header};
 use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
+
+use super::method::allow_header_value;
 
 /// 路由器路由错误的类别。
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -15,13 +18,18 @@ pub enum RouteErrorKind {
 pub struct RouteError {
     kind: RouteErrorKind,
     request: Request,
+    allowed_methods: Vec<Method>,
 }
 
 impl RouteError {
     /// 使用给定的请求和类别创建 [`RouteError`]。
     #[inline]
     pub fn new(request: Request, kind: RouteErrorKind) -> Self {
-        Self { kind, request }
+        Self {
+            kind,
+            request,
+            allowed_methods: Vec::new(),
+        }
     }
 
     /// 使用给定的请求创建 [`RouteError`]，类别为 [`RouteErrorKind::NotFound`]。
@@ -36,12 +44,27 @@ impl RouteError {
         Self::new(request, RouteErrorKind::MethodNotAllowed)
     }
 
+    /// 设置请求路径允许的请求方法。
+    #[inline]
+    pub fn with_allowed_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.allowed_methods = methods.into_iter().collect();
+        self
+    }
+
     /// 返回此错误的类别。
     #[inline]
     pub fn kind(&self) -> RouteErrorKind {
         self.kind
     }
 
+    /// 返回请求路径允许的请求方法。
+    ///
+    /// 仅在类别为 [`RouteErrorKind::MethodNotAllowed`] 时有意义。
+    #[inline]
+    pub fn allowed_methods(&self) -> &[Method] {
+        &self.allowed_methods
+    }
+
     /// 获取本次请求的引用。
     #[inline]
     pub fn request_ref(&self) -> &Request {
@@ -74,6 +97,25 @@ impl std::fmt::Display for RouteError {
 
 impl std::error::Error for RouteError {}
 
+impl ErrorResponse for RouteError {
+    fn status(&self) -> StatusCode {
+        match self.kind() {
+            RouteErrorKind::NotFound => StatusCode::NOT_FOUND,
+            RouteErrorKind::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+        }
+    }
+
+    fn to_response(&self) -> Response {
+        let mut response = self.status().into_response_always();
+        if self.kind == RouteErrorKind::MethodNotAllowed {
+            response
+                .headers_mut()
+                .insert(header::ALLOW, allow_header_value(&self.allowed_methods));
+        }
+        response
+    }
+}
+
 /// 路由器构建错误。
 #[derive(Debug, Clone)]
 pub enum RouterError {
@@ -0,0 +1,134 @@
+use boluo_core::BoxError;
+use boluo_core::request::Request;
+use boluo_core::response::Response;
+use boluo_core::service::ArcService;
+
+use super::RouterError;
+
+type FallbackService = ArcService<Request, Response, BoxError>;
+
+/// 路由器的后备服务。
+#[derive(Default, Clone)]
+pub(super) struct Fallback {
+    pub(super) not_found: Option<FallbackService>,
+    pub(super) method_not_allowed: Option<FallbackService>,
+}
+
+impl Fallback {
+    fn services_mut(&mut self) -> impl Iterator<Item = &mut FallbackService> {
+        self.not_found
+            .iter_mut()
+            .chain(self.method_not_allowed.iter_mut())
+    }
+
+    fn merge(&mut self, prefix: &str, other: Fallback) -> Result<(), RouterError> {
+        if (self.not_found.is_some() && other.not_found.is_some())
+            || (self.method_not_allowed.is_some() && other.method_not_allowed.is_some())
+        {
+            return Err(RouterError::PathConflict {
+                path: prefix.to_owned(),
+                message: "conflict with previously registered fallback".to_owned(),
+            });
+        }
+        if other.not_found.is_some() {
+            self.not_found = other.not_found;
+        }
+        if other.method_not_allowed.is_some() {
+            self.method_not_allowed = other.method_not_allowed;
+        }
+        Ok(())
+    }
+}
+
+/// 按路径前缀保存的后备服务。
+#[derive(Default, Clone)]
+pub(super) struct FallbackTable {
+    entries: Vec<(String, Fallback)>,
+}
+
+impl FallbackTable {
+    /// 获取指定前缀的后备服务，不存在则创建。
+    pub(super) fn get_or_create(&mut self, prefix: &str) -> &mut Fallback {
+        let index = match self
+            .entries
+            .iter()
+            .position(|(p, _)| same_prefix(p, prefix))
+        {
+            Some(index) => index,
+            None => {
+                self.entries.push((prefix.to_owned(), Fallback::default()));
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[index].1
+    }
+
+    /// 查找与请求路径匹配的后备服务，优先使用前缀最长的服务。
+    pub(super) fn find<F>(&self, path: &str, pick: F) -> Option<&FallbackService>
+    where
+        F: Fn(&Fallback) -> Option<&FallbackService>,
+    {
+        self.entries
+            .iter()
+            .filter_map(|(prefix, fallback)| {
+                let depth = match_prefix(prefix, path)?;
+                pick(fallback).map(|service| (depth, service))
+            })
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, service)| service)
+    }
+
+    /// 将另一个后备服务表合并到此表中，并使用给定函数转换合并的服务。
+    pub(super) fn merge<F>(
+        &mut self,
+        other: FallbackTable,
+        combine_prefix: impl Fn(&str) -> String,
+        f: F,
+    ) -> Result<(), RouterError>
+    where
+        F: Fn(FallbackService) -> FallbackService,
+    {
+        for (prefix, mut fallback) in other.entries {
+            fallback
+                .services_mut()
+                .for_each(|service| *service = f(service.clone()));
+            let prefix = combine_prefix(&prefix);
+            self.get_or_create(&prefix).merge(&prefix, fallback)?;
+        }
+        Ok(())
+    }
+
+    /// 返回一个迭代器，遍历表中的所有后备服务。
+    pub(super) fn services_mut(&mut self) -> impl Iterator<Item = &mut FallbackService> {
+        self.entries
+            .iter_mut()
+            .flat_map(|(_, fallback)| fallback.services_mut())
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|seg| !seg.is_empty())
+}
+
+fn same_prefix(a: &str, b: &str) -> bool {
+    segments(a).eq(segments(b))
+}
+
+/// 判断请求路径是否以给定前缀开头，匹配则返回前缀的段数。
+///
+/// 前缀中的捕获段可以匹配任意单个段，通配段可以匹配所有剩余的段。
+fn match_prefix(prefix: &str, path: &str) -> Option<usize> {
+    let mut path = segments(path);
+    let mut depth = 0;
+    for seg in segments(prefix) {
+        if seg.starts_with("{*") {
+            return Some(depth + 1);
+        }
+        let value = path.next()?;
+        if !(seg.starts_with('{') && seg.ends_with('}')) && seg != value {
+            return None;
+        }
+        depth += 1;
+    }
+    Some(depth)
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use boluo_core::http::Extensions;
+
+/// 路由器匹配的路由路径，例如 `/users/{id}`。
+///
+/// 路由器匹配成功后会将此类型插入请求扩展中。请求经过 [`Router::scope`] 嵌套的路由器时，
+/// 路径会包含所有外层前缀。请求仅匹配到嵌套路径时，路径以 `/{*}` 结尾。
+///
+/// 与原始请求路径相比，匹配的路由路径基数较低，适合作为日志、指标等的标签。
+///
+/// 此类型实现了 [`FromRequest`]，可以在处理程序中直接提取，请求没有经过路由器时提取失败。
+///
+/// # 例子
+///
+/// ```
+/// use boluo::extract::MatchedPath;
+/// use boluo::handler::handler_fn;
+/// use boluo::route::Router;
+///
+/// async fn user(path: MatchedPath) -> String {
+///     // 请求 `/api/users/42` 时返回 `/api/users/{id}`。
+///     path.to_string()
+/// }
+///
+/// let users = Router::new().route("/users/{id}", handler_fn(user));
+/// let router = Router::new().scope("/api", users);
+/// ```
+///
+/// [`Router::scope`]: super::Router::scope
+/// [`FromRequest`]: crate::extract::FromRequest
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchedPath {
+    path: Arc<str>,
+    scope: bool,
+}
+
+impl MatchedPath {
+    /// 获取匹配的路由路径。
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+}
+
+impl std::fmt::Display for MatchedPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+pub(super) fn insert_matched_path(extensions: &mut Extensions, path: &Arc<str>, scope: bool) {
+    let prefix = extensions
+        .get::<MatchedPath>()
+        .filter(|matched| matched.scope)
+        .map(|matched| scope_prefix(&matched.path).to_owned());
+
+    let path = match (prefix, scope) {
+        (None, false) => path.clone(),
+        (prefix, true) => {
+            let prefix = prefix.unwrap_or_default();
+            Arc::from(format!("{prefix}{}/{{*}}", scope_prefix(path)))
+        }
+        (Some(prefix), false) => Arc::from(format!("{prefix}{path}")),
+    };
+    extensions.insert(MatchedPath { path, scope });
+}
+
+/// 去掉嵌套路径末尾的 `{*}` 和 `/`。
+fn scope_prefix(path: &str) -> &str {
+    let path = path.strip_suffix("{*}").unwrap_or(path);
+    path.strip_suffix('/').unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+
+    use super::MatchedPath;
+    use crate::route::Router;
+
+    async fn matched_path(request: Request) -> String {
+        request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    }
+
+    async fn call(router: &Router, path: &str) -> String {
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        let response = router.call(request).await.unwrap();
+        let bytes = response.into_body().to_bytes().await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn nested_scope() {
+        let users = Router::new()
+            .route("/", handler_fn(matched_path))
+            .route("/{id}", handler_fn(matched_path));
+        let api = Router::new()
+            .scope("/users", users)
+            .scope("/files/", handler_fn(matched_path));
+        let router = Router::new()
+            .route("/health", handler_fn(matched_path))
+            .scope("/api/{version}", api);
+
+        assert_eq!(call(&router, "/health").await, "/health");
+        assert_eq!(
+            call(&router, "/api/v1/users/42").await,
+            "/api/{version}/users/{id}"
+        );
+        assert_eq!(
+            call(&router, "/api/v1/users/").await,
+            "/api/{version}/users/"
+        );
+        assert_eq!(
+            call(&router, "/api/v1/files/a/b").await,
+            "/api/{version}/files/{*}"
+        );
+        assert_eq!(
+            call(&router, "/api/v1/files/").await,
+            "/api/{version}/files/{*}"
+        );
+    }
+}
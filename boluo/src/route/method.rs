@@ -1,14 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
 use boluo_core::BoxError;
-use boluo_core::http::Method;
+use boluo_core::http::{HeaderValue, Method};
 use boluo_core::middleware::Middleware;
 use boluo_core::request::Request;
 use boluo_core::response::{IntoResponse, Response};
 use boluo_core::service::{ArcService, Service};
 
-use super::RouteError;
-
 #[derive(Debug, Default, Clone)]
 pub(super) struct MethodRouter {
     map: HashMap<Method, ArcService<Request, Response, BoxError>>,
@@ -74,7 +72,20 @@ impl MethodRouter {
         self.map.is_empty() && self.any.is_none()
     }
 
-    fn match_method(&self, method: &Method) -> Option<&ArcService<Request, Response, BoxError>> {
+    /// 返回已注册的请求方法，注册了 `GET` 时同时包含 `HEAD`。
+    pub(super) fn allowed_methods(&self) -> Vec<Method> {
+        let mut methods = self.map.keys().cloned().collect::<Vec<_>>();
+        if self.contains(&Method::GET) && !self.contains(&Method::HEAD) {
+            methods.push(Method::HEAD);
+        }
+        sort_methods(&mut methods);
+        methods
+    }
+
+    pub(super) fn match_method(
+        &self,
+        method: &Method,
+    ) -> Option<&ArcService<Request, Response, BoxError>> {
         if let Some(service) = self.map.get(method) {
             return Some(service);
         }
@@ -87,16 +98,19 @@ impl MethodRouter {
     }
 }
 
-impl Service<Request> for MethodRouter {
-    type Response = Response;
-    type Error = BoxError;
+/// 按名称排序请求方法，使 `Allow` 标头的值保持稳定。
+pub(super) fn sort_methods(methods: &mut [Method]) {
+    methods.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+}
 
-    async fn call(&self, request: Request) -> Result<Self::Response, Self::Error> {
-        let Some(service) = self.match_method(request.method()) else {
-            return Err(RouteError::method_not_allowed(request).into());
-        };
-        service.call(request).await
-    }
+/// 生成 `Allow` 标头的值。
+pub(super) fn allow_header_value(methods: &[Method]) -> HeaderValue {
+    let value = methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+    HeaderValue::from_str(&value).expect("method is a valid header value")
 }
 
 #[derive(Debug, Clone)]
@@ -122,6 +136,12 @@ impl Methods {
 /// 方法路由。
 ///
 /// 用于向路由器注册服务的类型，描述访问服务的请求方法。
+///
+/// 接收 [`GET`] 请求的服务同样会接收 [`HEAD`] 请求（除非单独注册了 [`HEAD`] 方法），
+/// 服务器会丢弃 [`HEAD`] 请求的响应主体，并保留包括 `Content-Length` 在内的其他标头。
+///
+/// [`GET`]: Method::GET
+/// [`HEAD`]: Method::HEAD
 #[derive(Debug, Clone)]
 pub struct MethodRoute<S> {
     methods: Methods,
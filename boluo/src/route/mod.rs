@@ -1,11 +1,14 @@
 //! 将请求转发到服务的类型和特征。
 
 mod error;
+mod fallback;
+mod matched_path;
 mod method;
 mod params;
 mod router;
 
 pub use error::{RouteError, RouteErrorKind, RouterError};
+pub use matched_path::MatchedPath;
 pub use method::{IntoMethodRoute, MethodRoute};
 pub use method::{any, connect, delete, get, head, options, patch, post, put, trace};
 pub use params::PathParams;
@@ -2,16 +2,19 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use boluo_core::BoxError;
-use boluo_core::http::Method;
 use boluo_core::http::uri::Uri;
+use boluo_core::http::{Method, StatusCode, header};
 use boluo_core::middleware::{Middleware, middleware_fn};
 use boluo_core::request::Request;
 use boluo_core::response::{IntoResponse, Response};
 use boluo_core::service::{ArcService, Service};
 use matchit::{Match, MatchError};
 
-use super::method::{ApplyMiddleware, MergeToMethodRouter, MethodRouter};
-use super::{IntoMethodRoute, MethodRoute, RouteError, RouterError};
+use super::fallback::FallbackTable;
+use super::method::{
+    ApplyMiddleware, MergeToMethodRouter, MethodRouter, allow_header_value, sort_methods,
+};
+use super::{IntoMethodRoute, MethodRoute, RouteError, RouteErrorKind, RouterError};
 
 pub(super) const PRIVATE_TAIL_PARAM: &str = "__private__boluo_tail_param";
 pub(super) const PRIVATE_TAIL_PARAM_CAPTURE: &str = "{*__private__boluo_tail_param}";
@@ -54,7 +57,11 @@ impl RouterInner {
     }
 
     fn get_path(&self, id: RouteId) -> Option<&str> {
-        self.id_to_path.get(&id).map(Arc::as_ref)
+        self.get_shared_path(id).map(Arc::as_ref)
+    }
+
+    fn get_shared_path(&self, id: RouteId) -> Option<&Arc<str>> {
+        self.id_to_path.get(&id)
     }
 
     fn get_id(&self, path: &str) -> Option<RouteId> {
@@ -190,6 +197,8 @@ impl<T> Endpoint<T> {
 pub struct Router {
     inner: RouterInner,
     table: HashMap<RouteId, Endpoint<MethodRouter>>,
+    fallbacks: FallbackTable,
+    auto_options: bool,
 }
 
 impl Router {
@@ -198,6 +207,86 @@ impl Router {
         Default::default()
     }
 
+    /// 设置请求路径不存在时使用的后备服务。
+    ///
+    /// 使用 [`Router::scope_merge`] 合并路由器时，被合并路由器的后备服务只处理带有对应前缀的请求，
+    /// 请求路径匹配多个后备服务时，优先使用前缀最长的后备服务。
+    ///
+    /// 未设置后备服务时，路由器将返回类别为 [`RouteErrorKind::NotFound`] 的 [`RouteError`]。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo::data::Json;
+    /// use boluo::handler::handler_fn;
+    /// use boluo::http::StatusCode;
+    /// use boluo::response::Html;
+    /// use boluo::route::Router;
+    ///
+    /// let api = Router::new()
+    ///     .route("/users", handler_fn(|| async { "users" }))
+    ///     // `/api` 下不存在的路径返回 JSON 格式的 404 响应。
+    ///     .fallback(handler_fn(|| async {
+    ///         (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "not found" })))
+    ///     }));
+    ///
+    /// Router::new()
+    ///     .scope_merge("/api", api)
+    ///     // 其余不存在的路径返回单页应用的首页。
+    ///     .fallback(handler_fn(|| async { Html("<div id=\"app\"></div>") }));
+    /// ```
+    pub fn fallback<S>(mut self, service: S) -> Self
+    where
+        S: Service<Request> + 'static,
+        S::Response: IntoResponse,
+        S::Error: Into<BoxError>,
+    {
+        self.fallbacks.get_or_create("/").not_found =
+            Some(boluo_core::util::__into_arc_service(service));
+        self
+    }
+
+    /// 设置请求路径存在但请求方法不被允许时使用的后备服务。
+    ///
+    /// 匹配规则与 [`Router::fallback`] 相同。
+    ///
+    /// 未设置后备服务时，路由器将返回类别为 [`RouteErrorKind::MethodNotAllowed`] 的
+    /// [`RouteError`]。
+    pub fn method_not_allowed_fallback<S>(mut self, service: S) -> Self
+    where
+        S: Service<Request> + 'static,
+        S::Response: IntoResponse,
+        S::Error: Into<BoxError>,
+    {
+        self.fallbacks.get_or_create("/").method_not_allowed =
+            Some(boluo_core::util::__into_arc_service(service));
+        self
+    }
+
+    /// 设置是否自动响应 `OPTIONS` 请求。
+    ///
+    /// 启用后，若请求路径没有注册 `OPTIONS` 方法，路由器将直接返回带有 `Allow` 标头的
+    /// `204 NO_CONTENT` 响应，并且 `405 METHOD_NOT_ALLOWED` 响应的 `Allow` 标头中也会包含
+    /// `OPTIONS` 方法。
+    ///
+    /// 通过 [`Router::scope`] 挂载的服务返回 `405 METHOD_NOT_ALLOWED` 错误时，同样会应用此设置。
+    /// 合并其他路由器时不会继承其设置。默认不启用。
+    ///
+    /// # 例子
+    ///
+    /// ```
+    /// use boluo::handler::handler_fn;
+    /// use boluo::route::{Router, get};
+    ///
+    /// let router = Router::new()
+    ///     .route("/", get(handler_fn(|| async {})))
+    ///     .auto_options(true);
+    /// ```
+    pub fn auto_options(mut self, enabled: bool) -> Self {
+        self.auto_options = enabled;
+        self
+    }
+
     /// 将服务添加到指定路径。
     ///
     /// # 恐慌
@@ -414,6 +503,11 @@ impl Router {
                 middleware.clone(),
             )?;
         }
+        self.fallbacks.merge(
+            other.fallbacks,
+            |prefix| prefix.to_owned(),
+            |service| boluo_core::util::__into_arc_service(middleware.clone().transform(service)),
+        )?;
         Ok(self)
     }
 
@@ -481,10 +575,15 @@ impl Router {
             let cpath = combine_path_segments(path, opath);
             self = self.add_endpoint_with(&cpath, endpoint, middleware.clone())?;
         }
+        self.fallbacks.merge(
+            other.fallbacks,
+            |prefix| combine_path_segments(path, prefix),
+            |service| boluo_core::util::__into_arc_service(middleware.clone().transform(service)),
+        )?;
         Ok(self)
     }
 
-    /// 为路由器内所有服务应用中间件。
+    /// 为路由器内所有服务（包括后备服务）应用中间件。
     pub fn with_for_each<M>(mut self, middleware: M) -> Self
     where
         M: Middleware<ArcService<Request, Response, BoxError>> + Clone,
@@ -492,13 +591,15 @@ impl Router {
         <M::Service as Service<Request>>::Response: IntoResponse,
         <M::Service as Service<Request>>::Error: Into<BoxError>,
     {
-        self.table.iter_mut().for_each(|(_, endpoint)| {
-            endpoint.as_mut().iter_mut().for_each(|(_, service)| {
+        self.table
+            .iter_mut()
+            .flat_map(|(_, endpoint)| endpoint.as_mut().iter_mut().map(|(_, service)| service))
+            .chain(self.fallbacks.services_mut())
+            .for_each(|service| {
                 *service = boluo_core::util::__into_arc_service(
                     middleware.clone().transform(service.clone()),
                 );
             });
-        });
         self
     }
 
@@ -680,25 +781,94 @@ impl Service<Request> for Router {
 
     async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
         let Ok(Match { value: id, params }) = self.inner.route_at(request.uri().path()) else {
-            return Err(RouteError::not_found(request).into());
+            return self.not_found(request).await;
         };
         let Some(endpoint) = self.table.get(id) else {
-            return Err(RouteError::not_found(request).into());
+            return self.not_found(request).await;
         };
 
         let (params, tail) = super::params::parse_path_params(params);
         super::params::insert_path_params(request.extensions_mut(), params);
+        if let Some(path) = self.inner.get_shared_path(*id) {
+            super::matched_path::insert_matched_path(
+                request.extensions_mut(),
+                path,
+                matches!(endpoint, Endpoint::Scope(_)),
+            );
+        }
 
-        match endpoint {
-            Endpoint::Route(service) => service.call(request).await,
-            Endpoint::Scope(service) => {
-                request = replace_request_path(request, tail.as_deref().unwrap_or_default());
-                service.call(request).await
+        let method_router = endpoint.as_ref();
+        let Some(service) = method_router.match_method(request.method()) else {
+            let mut methods = method_router.allowed_methods();
+            if self.auto_options {
+                if !methods.contains(&Method::OPTIONS) {
+                    methods.push(Method::OPTIONS);
+                    sort_methods(&mut methods);
+                }
+                if request.method() == Method::OPTIONS {
+                    return Ok(options_response(&methods));
+                }
+            }
+            let fallback = self.fallbacks.find(request.uri().path(), |fallback| {
+                fallback.method_not_allowed.as_ref()
+            });
+            if let Some(fallback) = fallback {
+                return fallback.call(request).await;
+            }
+            return Err(RouteError::method_not_allowed(request)
+                .with_allowed_methods(methods)
+                .into());
+        };
+
+        if let Endpoint::Scope(_) = endpoint {
+            request = replace_request_path(request, tail.as_deref().unwrap_or_default());
+            if self.auto_options {
+                return service.call(request).await.or_else(scope_auto_options);
             }
         }
+
+        service.call(request).await
     }
 }
 
+impl Router {
+    async fn not_found(&self, request: Request) -> Result<Response, BoxError> {
+        let fallback = self
+            .fallbacks
+            .find(request.uri().path(), |fallback| fallback.not_found.as_ref());
+        match fallback {
+            Some(fallback) => fallback.call(request).await,
+            None => Err(RouteError::not_found(request).into()),
+        }
+    }
+}
+
+/// 对嵌套服务返回的 `405 METHOD_NOT_ALLOWED` 错误应用自动响应 `OPTIONS` 请求的设置。
+fn scope_auto_options(error: BoxError) -> Result<Response, BoxError> {
+    let error = match error.downcast::<RouteError>() {
+        Ok(error) if error.kind() == RouteErrorKind::MethodNotAllowed => *error,
+        Ok(error) => return Err(error),
+        Err(error) => return Err(error),
+    };
+    let mut methods = error.allowed_methods().to_vec();
+    if !methods.contains(&Method::OPTIONS) {
+        methods.push(Method::OPTIONS);
+        sort_methods(&mut methods);
+    }
+    if error.request_ref().method() == Method::OPTIONS {
+        return Ok(options_response(&methods));
+    }
+    Err(error.with_allowed_methods(methods).into())
+}
+
+fn options_response(methods: &[Method]) -> Response {
+    let mut response = StatusCode::NO_CONTENT.into_response_always();
+    response
+        .headers_mut()
+        .insert(header::ALLOW, allow_header_value(methods));
+    response
+}
+
 /// 路由。
 ///
 /// 用于向路由器注册服务的类型，描述访问服务的请求路径和方法。
@@ -783,3 +953,222 @@ fn combine_path_segments(prefix: &str, path: &str) -> String {
     let path = path.strip_prefix('/').unwrap_or(path);
     format!("{prefix}/{path}")
 }
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{Method, StatusCode, header};
+    use boluo_core::request::Request;
+    use boluo_core::service::Service;
+
+    use super::Router;
+    use crate::data::Json;
+    use crate::response::error_responder;
+    use crate::route::{RouteError, RouteErrorKind, RouterError, get, post};
+
+    fn request(method: Method, path: &str) -> Request {
+        Request::builder()
+            .method(method)
+            .uri(path)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn call(router: &Router, method: Method, path: &str) -> (StatusCode, String) {
+        let response = router.call(request(method, path)).await.unwrap();
+        let status = response.status();
+        let bytes = response.into_body().to_bytes().await.unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn auto_options() {
+        let router = Router::new()
+            .route("/users", get(handler_fn(|| async {})))
+            .route("/users", post(handler_fn(|| async {})))
+            .auto_options(true);
+
+        let response = router
+            .call(request(Method::OPTIONS, "/users"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            response.headers()[header::ALLOW],
+            "GET, HEAD, OPTIONS, POST"
+        );
+
+        // 405 响应的 `Allow` 标头同样包含 `OPTIONS` 方法。
+        let error = router
+            .call(request(Method::DELETE, "/users"))
+            .await
+            .unwrap_err();
+        let response = error_responder().respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            response.headers()[header::ALLOW],
+            "GET, HEAD, OPTIONS, POST"
+        );
+    }
+
+    #[tokio::test]
+    async fn auto_options_scope() {
+        let api = Router::new().route("/users", post(handler_fn(|| async {})));
+        let router = Router::new().scope("/api", api).auto_options(true);
+
+        let response = router
+            .call(request(Method::OPTIONS, "/api/users"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[header::ALLOW], "OPTIONS, POST");
+
+        let error = router
+            .call(request(Method::DELETE, "/api/users"))
+            .await
+            .unwrap_err();
+        let response = error_responder().respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "OPTIONS, POST");
+    }
+
+    #[tokio::test]
+    async fn method_not_allowed_allow_header() {
+        let router = Router::new()
+            .route("/users", post(handler_fn(|| async {})))
+            .route("/users", get(handler_fn(|| async {})));
+
+        let error = router
+            .call(request(Method::OPTIONS, "/users"))
+            .await
+            .unwrap_err();
+        let response = error_responder().respond(error.as_ref()).unwrap();
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(response.headers()[header::ALLOW], "GET, HEAD, POST");
+    }
+
+    #[tokio::test]
+    async fn fallback_longest_prefix() {
+        let api = Router::new()
+            .route("/users", get(handler_fn(|| async { "users" })))
+            .fallback(handler_fn(|| async {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({ "error": "not found" })),
+                )
+            }));
+        let router = Router::new()
+            .scope_merge("/api", api)
+            .fallback(handler_fn(|| async { "spa" }));
+
+        assert_eq!(
+            call(&router, Method::GET, "/api/users").await,
+            (StatusCode::OK, "users".to_owned())
+        );
+        // `/api` 下不存在的路径使用前缀更长的后备服务。
+        assert_eq!(
+            call(&router, Method::GET, "/api/unknown").await,
+            (StatusCode::NOT_FOUND, r#"{"error":"not found"}"#.to_owned())
+        );
+        assert_eq!(
+            call(&router, Method::GET, "/apix").await,
+            (StatusCode::OK, "spa".to_owned())
+        );
+        assert_eq!(
+            call(&router, Method::GET, "/about/team").await,
+            (StatusCode::OK, "spa".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn fallback_param_prefix() {
+        let user = Router::new().fallback(handler_fn(|| async { "user" }));
+        let files = Router::new().fallback(handler_fn(|| async { "files" }));
+        let router = Router::new()
+            .scope_merge("/users/{id}", user)
+            .scope_merge("/files/{*path}", files);
+
+        // 捕获段匹配任意单个段。
+        assert_eq!(
+            call(&router, Method::GET, "/users/42/posts").await,
+            (StatusCode::OK, "user".to_owned())
+        );
+        // 通配段匹配所有剩余的段。
+        assert_eq!(
+            call(&router, Method::GET, "/files/a/b/c").await,
+            (StatusCode::OK, "files".to_owned())
+        );
+
+        // 没有匹配的后备服务时返回路由错误。
+        let error = router
+            .call(request(Method::GET, "/users"))
+            .await
+            .unwrap_err();
+        let error = error.downcast_ref::<RouteError>().unwrap();
+        assert_eq!(error.kind(), RouteErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn method_not_allowed_fallback() {
+        let admin = Router::new()
+            .route("/users", post(handler_fn(|| async { "created" })))
+            .method_not_allowed_fallback(handler_fn(|| async {
+                (StatusCode::METHOD_NOT_ALLOWED, "admin")
+            }));
+        let router = Router::new()
+            .route("/users", get(handler_fn(|| async { "users" })))
+            .scope_merge("/admin", admin);
+
+        assert_eq!(
+            call(&router, Method::GET, "/admin/users").await,
+            (StatusCode::METHOD_NOT_ALLOWED, "admin".to_owned())
+        );
+
+        // 路径不存在时不使用 405 后备服务。
+        let error = router
+            .call(request(Method::GET, "/admin/unknown"))
+            .await
+            .unwrap_err();
+        let error = error.downcast_ref::<RouteError>().unwrap();
+        assert_eq!(error.kind(), RouteErrorKind::NotFound);
+
+        // 前缀之外的请求不使用 405 后备服务。
+        let error = router
+            .call(request(Method::POST, "/users"))
+            .await
+            .unwrap_err();
+        let error = error.downcast_ref::<RouteError>().unwrap();
+        assert_eq!(error.kind(), RouteErrorKind::MethodNotAllowed);
+        assert_eq!(error.allowed_methods(), [Method::GET, Method::HEAD]);
+    }
+
+    #[test]
+    fn fallback_conflict() {
+        let fallback = || handler_fn(|| async { "fallback" });
+
+        let result = Router::new()
+            .fallback(fallback())
+            .try_merge(Router::new().fallback(fallback()));
+        assert!(matches!(result, Err(RouterError::PathConflict { path, .. }) if path == "/"));
+
+        let result = Router::new()
+            .scope_merge("/api", Router::new().fallback(fallback()))
+            .try_scope_merge("/api/", Router::new().fallback(fallback()));
+        assert!(matches!(result, Err(RouterError::PathConflict { path, .. }) if path == "/api/"));
+
+        // 不同种类或不同前缀的后备服务不会冲突。
+        assert!(
+            Router::new()
+                .fallback(fallback())
+                .try_merge(Router::new().method_not_allowed_fallback(fallback()))
+                .is_ok()
+        );
+        assert!(
+            Router::new()
+                .fallback(fallback())
+                .try_scope_merge("/api", Router::new().fallback(fallback()))
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use std::any::Any;
+use std::io::ErrorKind;
+
+use boluo_core::BoxError;
+
+/// 接收连接时发生的错误的类别，决定服务器如何处理该错误。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcceptErrorKind {
+    /// 单个连接的错误，例如连接在接收前被中止，服务器会立即继续接收连接。
+    Connection,
+    /// 资源暂时不足，例如文件描述符耗尽，服务器会等待一段时间后继续接收连接。
+    Resource,
+    /// 致命错误，服务器会停止运行并返回错误。
+    Fatal,
+}
+
+type ClassifyAcceptError<E> = Box<dyn Fn(&E) -> AcceptErrorKind + Send + Sync>;
+
+type OnAcceptError<E> = Box<dyn Fn(&E, AcceptErrorKind) + Send + Sync>;
+
+/// 接收连接时发生的错误的分类和报告方式，未设置时使用默认的方式。
+pub(super) struct AcceptErrorHandler<E> {
+    pub(super) classify: Option<ClassifyAcceptError<E>>,
+    pub(super) report: Option<OnAcceptError<E>>,
+}
+
+impl<E> Default for AcceptErrorHandler<E> {
+    fn default() -> Self {
+        Self {
+            classify: None,
+            report: None,
+        }
+    }
+}
+
+impl<E: 'static> AcceptErrorHandler<E> {
+    pub(super) fn classify(&self, error: &E) -> AcceptErrorKind {
+        match &self.classify {
+            Some(classify) => classify(error),
+            None => classify(error),
+        }
+    }
+
+    pub(super) fn report(&self, error: &E, kind: AcceptErrorKind) {
+        match &self.report {
+            Some(report) => report(error, kind),
+            None => report(error, kind),
+        }
+    }
+}
+
+/// 默认的错误分类，只识别 [`std::io::Error`]，其他错误均视为致命错误。
+fn classify<E: 'static>(error: &E) -> AcceptErrorKind {
+    let mut next = as_error(error);
+    while let Some(error) = next {
+        if let Some(error) = error.downcast_ref::<std::io::Error>() {
+            return classify_io(error);
+        }
+        next = error.source();
+    }
+    AcceptErrorKind::Fatal
+}
+
+/// 默认的错误报告方式，启用 `tracing` 功能时记录可恢复的错误。
+fn report<E: 'static>(
+    #[cfg_attr(not(feature = "tracing"), allow(unused))] error: &E,
+    #[cfg_attr(not(feature = "tracing"), allow(unused))] kind: AcceptErrorKind,
+) {
+    #[cfg(feature = "tracing")]
+    if kind != AcceptErrorKind::Fatal {
+        match as_error(error) {
+            Some(error) => {
+                tracing::warn!(error = %error, kind = ?kind, "failed to accept connection")
+            }
+            None => tracing::warn!(kind = ?kind, "failed to accept connection"),
+        }
+    }
+}
+
+fn as_error<E: 'static>(error: &E) -> Option<&(dyn std::error::Error + 'static)> {
+    let error = error as &dyn Any;
+    if let Some(error) = error.downcast_ref::<std::io::Error>() {
+        return Some(error);
+    }
+    if let Some(error) = error.downcast_ref::<BoxError>() {
+        return Some(error.as_ref());
+    }
+    None
+}
+
+fn classify_io(error: &std::io::Error) -> AcceptErrorKind {
+    match error.kind() {
+        ErrorKind::ConnectionAborted
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionRefused
+        | ErrorKind::HostUnreachable
+        | ErrorKind::NetworkUnreachable
+        | ErrorKind::NetworkDown
+        | ErrorKind::TimedOut
+        | ErrorKind::Interrupted
+        | ErrorKind::WouldBlock => AcceptErrorKind::Connection,
+        ErrorKind::OutOfMemory => AcceptErrorKind::Resource,
+        _ => classify_os(error.raw_os_error()),
+    }
+}
+
+#[cfg(unix)]
+fn classify_os(code: Option<i32>) -> AcceptErrorKind {
+    match code {
+        Some(libc::EMFILE | libc::ENFILE | libc::ENOBUFS | libc::ENOMEM) => {
+            AcceptErrorKind::Resource
+        }
+        // Linux 的 accept 会将新连接上的网络错误传递给调用者，这些错误只影响单个连接
+        Some(libc::EPROTO | libc::ENOPROTOOPT | libc::EHOSTDOWN | libc::EOPNOTSUPP) => {
+            AcceptErrorKind::Connection
+        }
+        _ => AcceptErrorKind::Fatal,
+    }
+}
+
+#[cfg(not(unix))]
+fn classify_os(_code: Option<i32>) -> AcceptErrorKind {
+    AcceptErrorKind::Fatal
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Error, ErrorKind};
+
+    use boluo_core::BoxError;
+
+    use super::{AcceptErrorKind, classify};
+
+    #[test]
+    fn classify_errors() {
+        let aborted = Error::from(ErrorKind::ConnectionAborted);
+        assert_eq!(classify(&aborted), AcceptErrorKind::Connection);
+
+        #[cfg(unix)]
+        {
+            let emfile = Error::from_raw_os_error(libc::EMFILE);
+            assert_eq!(classify(&emfile), AcceptErrorKind::Resource);
+            let boxed = BoxError::from(Error::from_raw_os_error(libc::ENFILE));
+            assert_eq!(classify(&boxed), AcceptErrorKind::Resource);
+        }
+
+        let closed = Error::from(ErrorKind::InvalidInput);
+        assert_eq!(classify(&closed), AcceptErrorKind::Fatal);
+        assert_eq!(classify(&BoxError::from("unknown")), AcceptErrorKind::Fatal);
+    }
+}
@@ -2,9 +2,9 @@ use std::convert::Infallible;
 
 use boluo_core::BoxError;
 use boluo_core::body::Body;
-use boluo_core::http::{Extensions, StatusCode};
+use boluo_core::http::{Extensions, Method};
 use boluo_core::request::Request;
-use boluo_core::response::{IntoResponse, Response};
+use boluo_core::response::{ErrorResponder, IntoResponse, Response};
 use boluo_core::service::{ArcService, Service, ServiceExt};
 use boluo_core::upgrade::{OnUpgrade, Upgraded};
 use hyper::Request as HyperRequest;
@@ -14,9 +14,22 @@ use hyper::upgrade::OnUpgrade as HyperOnUpgrade;
 use hyper_util::rt::TokioIo;
 use tokio_util::compat::TokioAsyncReadCompatExt;
 
+use super::conn::{MinBodyRate, MinRateBody};
+use crate::response::strip_body;
+
 #[derive(Clone)]
 pub(super) struct ServiceToHyper {
     service: ArcService<Request, Response, Infallible>,
+    responder: ErrorResponder,
+    min_body_rate: Option<MinBodyRate>,
+}
+
+impl ServiceToHyper {
+    /// 设置读取请求主体的最低速率。
+    pub(super) fn min_body_rate(mut self, rate: Option<MinBodyRate>) -> Self {
+        self.min_body_rate = rate;
+        self
+    }
 }
 
 impl Service<HyperRequest<Incoming>> for ServiceToHyper {
@@ -24,43 +37,62 @@ impl Service<HyperRequest<Incoming>> for ServiceToHyper {
     type Error = Infallible;
 
     async fn call(&self, request: HyperRequest<Incoming>) -> Result<Self::Response, Self::Error> {
+        let is_head = request.method() == Method::HEAD;
+        let mut request = request_from_hyper(request, self.min_body_rate);
+        request.extensions_mut().insert(self.responder.clone());
         self.service
-            .call(request_from_hyper(request))
+            .call(request)
             .await
+            .map(|response| {
+                if is_head {
+                    strip_body(response)
+                } else {
+                    response
+                }
+            })
             .map(response_to_hyper)
     }
 }
 
-pub(super) fn service_to_hyper<S>(service: S) -> ServiceToHyper
+pub(super) fn service_to_hyper<S>(service: S, responder: ErrorResponder) -> ServiceToHyper
 where
     S: Service<Request> + 'static,
     S::Response: IntoResponse,
     S::Error: Into<BoxError>,
 {
     ServiceToHyper {
-        service: into_arc_service(service),
+        service: into_arc_service(service, responder.clone()),
+        responder,
+        min_body_rate: None,
     }
 }
 
-fn into_arc_service<S>(service: S) -> ArcService<Request, Response, Infallible>
+fn into_arc_service<S>(
+    service: S,
+    responder: ErrorResponder,
+) -> ArcService<Request, Response, Infallible>
 where
     S: Service<Request> + 'static,
     S::Response: IntoResponse,
     S::Error: Into<BoxError>,
 {
     boluo_core::util::__try_downcast(service).unwrap_or_else(|service| {
-        let service = service.map_result(|result| {
+        let service = service.map_result(move |result| {
             result
                 .into_response()
-                .or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+                .or_else(|e| Ok(responder.respond_always(&*e)))
         });
         service.boxed_arc()
     })
 }
 
-fn request_from_hyper(request: HyperRequest<Incoming>) -> Request {
+fn request_from_hyper(request: HyperRequest<Incoming>, rate: Option<MinBodyRate>) -> Request {
     let (parts, body) = request.into_parts();
-    let mut request = Request::new(Body::new(body));
+    let body = match rate {
+        Some(rate) => Body::new(MinRateBody::new(body, rate)),
+        None => Body::new(body),
+    };
+    let mut request = Request::new(body);
     *request.method_mut() = parts.method;
     *request.uri_mut() = parts.uri;
     *request.version_mut() = parts.version;
@@ -94,3 +126,74 @@ fn replace_hyper_upgrade(mut extensions: Extensions) -> Extensions {
     }
     extensions
 }
+
+#[cfg(all(test, feature = "http1"))]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::request::Request;
+    use boluo_core::response::{ErrorResponder, Response};
+    use boluo_core::service::Service;
+    use hyper_util::rt::TokioIo;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::service_to_hyper;
+
+    /// 通过 HTTP/1.1 连接发送请求，返回完整的响应。
+    async fn send(method: &str, path: &str) -> String {
+        let service = service_to_hyper(
+            handler_fn(|request: Request| async move {
+                match request.uri().path() {
+                    "/stream" => {
+                        let stream = futures_util::stream::iter(
+                            ["hello", " world"].map(Ok::<_, std::convert::Infallible>),
+                        );
+                        Response::new(Body::from_data_stream(stream))
+                    }
+                    _ => Response::new(Body::from("hello world")),
+                }
+            }),
+            ErrorResponder::new(),
+        );
+
+        let (mut client, server) = tokio::io::duplex(4096);
+        tokio::spawn(hyper::server::conn::http1::Builder::new().serve_connection(
+            TokioIo::new(server),
+            hyper::service::service_fn(move |request| {
+                let service = service.clone();
+                async move { service.call(request).await }
+            }),
+        ));
+
+        let request =
+            format!("{method} {path} HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n");
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        response.to_ascii_lowercase()
+    }
+
+    #[tokio::test]
+    async fn head_exact_size() {
+        let response = send("HEAD", "/").await;
+        assert!(response.contains("content-length: 11\r\n"), "{response}");
+        assert!(response.ends_with("\r\n\r\n"), "{response}");
+
+        let response = send("GET", "/").await;
+        assert!(response.contains("content-length: 11\r\n"), "{response}");
+        assert!(response.ends_with("\r\n\r\nhello world"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn head_stream() {
+        let response = send("HEAD", "/stream").await;
+        assert!(!response.contains("content-length"), "{response}");
+        assert!(response.ends_with("\r\n\r\n"), "{response}");
+
+        let response = send("GET", "/stream").await;
+        assert!(
+            response.contains("transfer-encoding: chunked\r\n"),
+            "{response}"
+        );
+    }
+}
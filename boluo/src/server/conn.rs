@@ -0,0 +1,380 @@
+use std::io::IoSlice;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use boluo_core::BoxError;
+use boluo_core::body::{Bytes, Frame, HttpBody, SizeHint};
+use boluo_core::http::StatusCode;
+use boluo_core::response::ErrorResponse;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::Notify;
+use tokio::time::{Instant, Sleep};
+
+/// 连接级别的限制。
+#[derive(Debug, Default, Clone, Copy)]
+pub(super) struct ConnectionLimits {
+    pub(super) idle_timeout: Option<Duration>,
+    pub(super) max_lifetime: Option<Duration>,
+    pub(super) max_requests: Option<usize>,
+    pub(super) min_body_rate: Option<MinBodyRate>,
+}
+
+/// 读取请求主体的最低速率。
+#[derive(Debug, Clone, Copy)]
+pub(super) struct MinBodyRate {
+    pub(super) bytes_per_second: u64,
+    pub(super) grace: Duration,
+}
+
+/// 连接的状态，用于判断连接是否应该关闭。
+#[derive(Debug)]
+pub(super) struct Connection {
+    limits: ConnectionLimits,
+    start: Instant,
+    /// 最后一次活动距离连接建立的毫秒数。
+    last_active: AtomicU64,
+    in_flight: AtomicUsize,
+    requests: AtomicUsize,
+    request_limit: Notify,
+}
+
+impl Connection {
+    pub(super) fn new(limits: ConnectionLimits) -> Self {
+        Self {
+            limits,
+            start: Instant::now(),
+            last_active: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            requests: AtomicUsize::new(0),
+            request_limit: Notify::new(),
+        }
+    }
+
+    /// 记录连接上的活动。
+    fn touch(&self) {
+        if self.limits.idle_timeout.is_some() {
+            let elapsed = self.start.elapsed().as_millis() as u64;
+            self.last_active.fetch_max(elapsed, Ordering::Relaxed);
+        }
+    }
+
+    /// 记录连接上的新请求，返回的守卫被丢弃时请求结束。
+    pub(super) fn request(self: &Arc<Self>) -> RequestGuard {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let requests = self.requests.fetch_add(1, Ordering::AcqRel) + 1;
+        if self.limits.max_requests == Some(requests) {
+            self.request_limit.notify_one();
+        }
+        RequestGuard(self.clone())
+    }
+
+    /// 等待直到连接应该被关闭。
+    pub(super) async fn closed(&self) {
+        // 存活时间过长导致溢出时，视为没有期限
+        let lifetime = self
+            .limits
+            .max_lifetime
+            .and_then(|lifetime| self.start.checked_add(lifetime));
+        tokio::select! {
+            _ = optional(self.limits.idle_timeout.map(|timeout| self.idle(timeout))) => {}
+            _ = optional(lifetime.map(tokio::time::sleep_until)) => {}
+            _ = optional(self.limits.max_requests.map(|_| self.request_limit.notified())) => {}
+        }
+    }
+
+    async fn idle(&self, timeout: Duration) {
+        loop {
+            let last_active = Duration::from_millis(self.last_active.load(Ordering::Relaxed));
+            let Some(deadline) = self
+                .start
+                .checked_add(last_active)
+                .and_then(|instant| instant.checked_add(timeout))
+            else {
+                // 超时时间过长导致溢出时，视为没有期限
+                return std::future::pending().await;
+            };
+            if deadline > Instant::now() {
+                tokio::time::sleep_until(deadline).await;
+            } else if self.in_flight.load(Ordering::Acquire) > 0 {
+                // 正在处理请求的连接不是空闲连接
+                self.touch();
+            } else {
+                return;
+            }
+        }
+    }
+}
+
+async fn optional<F: Future>(future: Option<F>) {
+    match future {
+        Some(future) => {
+            future.await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+pub(super) struct RequestGuard(Arc<Connection>);
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+        self.0.touch();
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// 记录读写活动的连接。
+    pub(super) struct ConnectionIo<IO> {
+        #[pin]
+        io: IO,
+        connection: Arc<Connection>,
+    }
+}
+
+impl<IO> ConnectionIo<IO> {
+    pub(super) fn new(io: IO, connection: Arc<Connection>) -> Self {
+        Self { io, connection }
+    }
+}
+
+impl<IO: AsyncRead> AsyncRead for ConnectionIo<IO> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.project();
+        let filled = buf.filled().len();
+        let poll = this.io.poll_read(cx, buf);
+        if buf.filled().len() > filled {
+            this.connection.touch();
+        }
+        poll
+    }
+}
+
+impl<IO: AsyncWrite> AsyncWrite for ConnectionIo<IO> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.project();
+        let poll = this.io.poll_write(cx, buf);
+        if matches!(poll, Poll::Ready(Ok(n)) if n > 0) {
+            this.connection.touch();
+        }
+        poll
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.project();
+        let poll = this.io.poll_write_vectored(cx, bufs);
+        if matches!(poll, Poll::Ready(Ok(n)) if n > 0) {
+            this.connection.touch();
+        }
+        poll
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.project().io.poll_shutdown(cx)
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// 限制读取速率的请求主体。
+    ///
+    /// 只统计等待客户端发送数据的时间，服务读取主体较慢时不会触发限制。
+    pub(super) struct MinRateBody<B> {
+        #[pin]
+        inner: B,
+        rate: MinBodyRate,
+        received: u64,
+        waited: Duration,
+        waiting_since: Option<Instant>,
+        #[pin]
+        sleep: Option<Sleep>,
+    }
+}
+
+impl<B> MinRateBody<B> {
+    pub(super) fn new(inner: B, rate: MinBodyRate) -> Self {
+        Self {
+            inner,
+            rate,
+            received: 0,
+            waited: Duration::ZERO,
+            waiting_since: None,
+            sleep: None,
+        }
+    }
+}
+
+impl<B> HttpBody for MinRateBody<B>
+where
+    B: HttpBody<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        match this.inner.poll_frame(cx) {
+            Poll::Pending => {
+                let now = Instant::now();
+                let waiting_since = *this.waiting_since.get_or_insert(now);
+                // 已接收的数据允许等待的总时间，溢出时视为没有期限
+                let deadline = Duration::try_from_secs_f64(
+                    *this.received as f64 / this.rate.bytes_per_second as f64,
+                )
+                .ok()
+                .and_then(|allowed| allowed.checked_add(this.rate.grace))
+                .and_then(|allowed| {
+                    waiting_since.checked_add(allowed.saturating_sub(*this.waited))
+                });
+                let Some(deadline) = deadline else {
+                    this.sleep.set(None);
+                    return Poll::Pending;
+                };
+                if deadline <= now {
+                    this.sleep.set(None);
+                    return Poll::Ready(Some(Err(BodyRateError { _priv: () }.into())));
+                }
+                match this.sleep.as_mut().as_pin_mut() {
+                    Some(sleep) => sleep.reset(deadline),
+                    None => this.sleep.set(Some(tokio::time::sleep_until(deadline))),
+                }
+                let elapsed = this
+                    .sleep
+                    .as_mut()
+                    .as_pin_mut()
+                    .is_some_and(|sleep| sleep.poll(cx).is_ready());
+                if elapsed {
+                    this.sleep.set(None);
+                    return Poll::Ready(Some(Err(BodyRateError { _priv: () }.into())));
+                }
+                Poll::Pending
+            }
+            Poll::Ready(frame) => {
+                if let Some(waiting_since) = this.waiting_since.take() {
+                    *this.waited += waiting_since.elapsed();
+                }
+                this.sleep.set(None);
+                if let Some(Ok(frame)) = &frame {
+                    *this.received += frame.data_ref().map_or(0, |data| data.len() as u64);
+                }
+                Poll::Ready(frame.map(|frame| frame.map_err(Into::into)))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// 客户端发送请求主体的速率低于服务器要求的最低速率。
+///
+/// 错误对应的响应为 `408 REQUEST_TIMEOUT`。
+pub struct BodyRateError {
+    _priv: (),
+}
+
+impl std::fmt::Debug for BodyRateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BodyRateError").finish()
+    }
+}
+
+impl std::fmt::Display for BodyRateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("request body was received too slowly")
+    }
+}
+
+impl std::error::Error for BodyRateError {}
+
+impl ErrorResponse for BodyRateError {
+    fn status(&self) -> StatusCode {
+        StatusCode::REQUEST_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use boluo_core::body::Body;
+    use futures_util::StreamExt;
+
+    use super::{BodyRateError, Connection, ConnectionLimits, MinBodyRate, MinRateBody};
+
+    #[tokio::test(start_paused = true)]
+    async fn min_body_rate() {
+        let rate = MinBodyRate {
+            bytes_per_second: 100,
+            grace: Duration::from_secs(1),
+        };
+        let body = |delays: &'static [u64]| {
+            let stream = futures_util::stream::iter(delays).then(|delay| async move {
+                tokio::time::sleep(Duration::from_millis(*delay)).await;
+                Ok::<_, std::convert::Infallible>(vec![0; 100])
+            });
+            Body::new(MinRateBody::new(Body::from_data_stream(stream), rate))
+        };
+
+        // 每块数据允许等待 1 秒，并累积宽限时间。
+        let bytes = body(&[500, 1200, 1000]).to_bytes().await.unwrap();
+        assert_eq!(bytes.len(), 300);
+
+        let error = body(&[500, 3000]).to_bytes().await.unwrap_err();
+        assert!(error.is::<BodyRateError>());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn overflowing_deadlines() {
+        // 计算期限溢出时视为没有期限，而不是触发 panic。
+        let connection = Connection::new(ConnectionLimits {
+            idle_timeout: Some(Duration::MAX),
+            max_lifetime: Some(Duration::MAX),
+            ..Default::default()
+        });
+        let closed = tokio::time::timeout(Duration::from_secs(3600), connection.closed());
+        assert!(closed.await.is_err());
+
+        let rate = MinBodyRate {
+            bytes_per_second: 1,
+            grace: Duration::MAX,
+        };
+        let stream = futures_util::stream::once(async {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            Ok::<_, std::convert::Infallible>(vec![0; 100])
+        });
+        let body = Body::new(MinRateBody::new(Body::from_data_stream(stream), rate));
+        assert_eq!(body.to_bytes().await.unwrap().len(), 100);
+    }
+}
@@ -1,4 +1,6 @@
 use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
 use tokio::sync::watch::{self, Receiver, Sender};
@@ -8,27 +10,49 @@ use tokio::sync::watch::{self, Receiver, Sender};
 pub struct GracefulShutdown {
     tx: Sender<()>,
     rx: Receiver<()>,
+    connections: Arc<AtomicUsize>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics::Metrics>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub(super) struct Monitor {
     rx: Receiver<()>,
+    connections: Arc<AtomicUsize>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics::Metrics>,
 }
 
 impl Monitor {
-    /// 监视任务并在接收到关机信号时执行关机操作。
-    pub(super) async fn watch<T>(mut self, task: T, shutdown: impl FnOnce(Pin<&mut T>)) -> T::Output
+    /// 监视任务并在接收到关机信号或 `close` 完成时执行关机操作。
+    pub(super) async fn watch<T>(
+        mut self,
+        task: T,
+        close: impl Future<Output = ()>,
+        shutdown: impl FnOnce(Pin<&mut T>),
+    ) -> T::Output
     where
         T: Future,
     {
         let mut task = std::pin::pin!(task);
         tokio::select! {
-            _ = self.rx.changed() => {
-                shutdown(task.as_mut());
-                task.await
-            }
-            v = task.as_mut() => v
+            _ = self.rx.changed() => {}
+            _ = close => {}
+            v = task.as_mut() => return v,
         }
+        #[cfg(feature = "metrics")]
+        let _draining = self
+            .metrics
+            .as_ref()
+            .map(crate::metrics::Metrics::draining_connection);
+        shutdown(task.as_mut());
+        task.await
+    }
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.connections.fetch_sub(1, Ordering::AcqRel);
     }
 }
 
@@ -36,16 +60,38 @@ impl GracefulShutdown {
     /// 创建新的 `GracefulShutdown` 实例。
     pub(super) fn new() -> Self {
         let (tx, rx) = watch::channel(());
-        Self { tx, rx }
+        Self {
+            tx,
+            rx,
+            connections: Arc::new(AtomicUsize::new(0)),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// 设置记录正在关闭的连接的 `Metrics`。
+    #[cfg(feature = "metrics")]
+    pub(super) fn with_metrics(mut self, metrics: Option<crate::metrics::Metrics>) -> Self {
+        self.metrics = metrics;
+        self
     }
 
-    /// 创建一个 `Monitor` 实例，用于监视任务。
+    /// 创建一个 `Monitor` 实例，用于监视连接。
     pub(super) fn monitor(&self) -> Monitor {
+        self.connections.fetch_add(1, Ordering::AcqRel);
         Monitor {
             rx: self.rx.clone(),
+            connections: self.connections.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
         }
     }
 
+    /// 获取服务器打开的连接数。
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::Acquire)
+    }
+
     /// 发出关机信号，在指定时间内等待服务器完成剩余请求。
     ///
     /// - 返回 Ok：表示服务器完成了所有剩余请求。
@@ -54,14 +100,21 @@ impl GracefulShutdown {
         self,
         timeout: impl Into<Option<Duration>>,
     ) -> Result<(), GracefulShutdownTimeout> {
-        let GracefulShutdown { tx, rx } = self;
+        let GracefulShutdown {
+            tx,
+            rx,
+            connections,
+            ..
+        } = self;
 
         drop(rx);
         tx.send_modify(|_| {});
 
         tokio::select! {
             _ = tx.closed() => Ok(()),
-            _ = sleep(timeout) => Err(GracefulShutdownTimeout { _priv: () }),
+            _ = sleep(timeout) => Err(GracefulShutdownTimeout {
+                connections: connections.load(Ordering::Acquire),
+            }),
         }
     }
 }
@@ -76,18 +129,31 @@ async fn sleep(timeout: impl Into<Option<Duration>>) {
 
 /// 优雅关机超时。
 pub struct GracefulShutdownTimeout {
-    _priv: (),
+    connections: usize,
+}
+
+impl GracefulShutdownTimeout {
+    /// 获取超时时仍未关闭的连接数。
+    pub fn connections(&self) -> usize {
+        self.connections
+    }
 }
 
 impl std::fmt::Debug for GracefulShutdownTimeout {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("GracefulShutdownTimeout").finish()
+        f.debug_struct("GracefulShutdownTimeout")
+            .field("connections", &self.connections)
+            .finish()
     }
 }
 
 impl std::fmt::Display for GracefulShutdownTimeout {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "server graceful shutdown timeout")
+        write!(
+            f,
+            "server graceful shutdown timeout ({} connections still draining)",
+            self.connections
+        )
     }
 }
 
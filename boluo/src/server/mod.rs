@@ -1,29 +1,46 @@
 //! HTTP 服务器。
 
+mod accept;
 mod compat;
+mod conn;
 mod graceful_shutdown;
 
+pub use accept::AcceptErrorKind;
+pub use conn::BodyRateError;
 pub use graceful_shutdown::{GracefulShutdown, GracefulShutdownTimeout};
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use boluo_core::BoxError;
 use boluo_core::request::Request;
-use boluo_core::response::IntoResponse;
+use boluo_core::response::{ErrorResponder, IntoResponse};
 use boluo_core::service::Service;
 use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
 use hyper_util::server::conn::auto::Builder;
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::Semaphore;
+
+use self::conn::{Connection, ConnectionIo, ConnectionLimits, MinBodyRate};
 
 use crate::listener::Listener;
+use crate::middleware::{ConcurrencyLimit, Middleware};
 
 /// HTTP 服务器。
-pub struct Server<L> {
+pub struct Server<L: Listener> {
     listener: L,
     builder: Builder<TokioExecutor>,
+    error_responder: ErrorResponder,
+    concurrency_limit: Option<ConcurrencyLimit>,
+    max_connections: Option<usize>,
+    connection_limits: ConnectionLimits,
+    accept_backoff: Duration,
+    accept_errors: accept::AcceptErrorHandler<L::Error>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::metrics::Metrics>,
 }
 
-impl<L> std::fmt::Debug for Server<L> {
+impl<L: Listener> std::fmt::Debug for Server<L> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Server")
             .field("listener", &std::any::type_name::<L>())
@@ -36,6 +53,7 @@ where
     L: Listener,
     L::IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
     L::Addr: Clone + Send + Sync + 'static,
+    L::Error: 'static,
 {
     /// 使用指定的监听器创建服务器。
     pub fn new(listener: L) -> Self {
@@ -48,7 +66,166 @@ where
         {
             builder.http2().timer(TokioTimer::default());
         }
-        Self { listener, builder }
+        Self {
+            listener,
+            builder,
+            error_responder: crate::response::error_responder(),
+            concurrency_limit: None,
+            max_connections: None,
+            connection_limits: ConnectionLimits::default(),
+            accept_backoff: Duration::from_secs(1),
+            accept_errors: Default::default(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// 设置将服务返回的错误转换为响应的 [`ErrorResponder`]。
+    ///
+    /// 默认使用 [`error_responder`] 创建的实例，错误链中没有已注册的错误类型时，
+    /// 服务器将返回 `500 INTERNAL_SERVER_ERROR` 响应。
+    ///
+    /// 服务器会将此实例添加到每个请求的扩展中，需要确定错误对应的状态码的中间件会使用它。
+    ///
+    /// [`error_responder`]: crate::response::error_responder
+    pub fn error_responder(&mut self, responder: ErrorResponder) -> &mut Self {
+        self.error_responder = responder;
+        self
+    }
+
+    /// 设置整个服务器同时处理的请求数上限。
+    ///
+    /// 限制作用于所有连接上的请求，与路由上应用的 [`ConcurrencyLimit`] 相互独立。
+    /// 默认不限制同时处理的请求数。
+    ///
+    /// # 例子
+    ///
+    /// ```no_run
+    /// use boluo::middleware::ConcurrencyLimit;
+    /// use boluo::route::Router;
+    /// use boluo::server::Server;
+    /// use tokio::net::TcpListener;
+    ///
+    /// # async fn run() {
+    /// let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+    ///
+    /// Server::new(listener)
+    ///     .concurrency_limit(ConcurrencyLimit::new(1024).queue(256))
+    ///     .run(Router::new())
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn concurrency_limit(&mut self, limit: ConcurrencyLimit) -> &mut Self {
+        self.concurrency_limit = Some(limit);
+        self
+    }
+
+    /// 设置服务器同时打开的连接数上限。
+    ///
+    /// 打开的连接数达到上限时，服务器会暂停接收新连接，直到有连接关闭。默认不限制连接数。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `max` 为零或超过 [`Semaphore::MAX_PERMITS`] 时会触发 panic。
+    pub fn max_connections(&mut self, max: usize) -> &mut Self {
+        assert!(
+            max > 0 && max <= Semaphore::MAX_PERMITS,
+            "connection limit must be between 1 and {}",
+            Semaphore::MAX_PERMITS
+        );
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// 设置连接的空闲超时时间。
+    ///
+    /// 连接上没有正在处理的请求，并且在指定时间内没有读写数据时，服务器会优雅地关闭连接。
+    /// 默认不限制空闲时间。
+    pub fn idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connection_limits.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置连接的最长存活时间。
+    ///
+    /// 连接建立超过指定时间后，服务器会优雅地关闭连接，正在处理的请求不受影响。默认不限制存活时间。
+    pub fn max_connection_lifetime(&mut self, lifetime: Duration) -> &mut Self {
+        self.connection_limits.max_lifetime = Some(lifetime);
+        self
+    }
+
+    /// 设置每个连接最多处理的请求数。
+    ///
+    /// 连接接收的请求数达到上限后，服务器会在完成正在处理的请求后关闭连接，HTTP/2 连接会发送
+    /// `GOAWAY` 帧。默认不限制请求数。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `max` 为零时会触发 panic。
+    pub fn max_requests_per_connection(&mut self, max: usize) -> &mut Self {
+        assert!(max > 0, "request limit per connection must be positive");
+        self.connection_limits.max_requests = Some(max);
+        self
+    }
+
+    /// 设置客户端发送请求主体的最低速率，用于防御慢速攻击。
+    ///
+    /// 读取请求主体时只统计等待客户端发送数据的时间，等待时间超过 `grace` 加上已接收数据按
+    /// `bytes_per_second` 计算的时间后，读取主体会返回 [`BodyRateError`]。默认不限制速率。
+    ///
+    /// # 恐慌
+    ///
+    /// 当 `bytes_per_second` 为零时会触发 panic。
+    pub fn min_request_body_rate(&mut self, bytes_per_second: u64, grace: Duration) -> &mut Self {
+        assert!(bytes_per_second > 0, "minimum body rate must be positive");
+        self.connection_limits.min_body_rate = Some(MinBodyRate {
+            bytes_per_second,
+            grace,
+        });
+        self
+    }
+
+    /// 设置接收连接时资源暂时不足的等待时间。
+    ///
+    /// 类别为 [`AcceptErrorKind::Resource`] 的错误发生后，服务器会等待指定的时间再继续接收连接，
+    /// 避免在文件描述符耗尽等情况下反复重试。默认值为 1 秒。
+    pub fn accept_backoff(&mut self, duration: Duration) -> &mut Self {
+        self.accept_backoff = duration;
+        self
+    }
+
+    /// 设置对接收连接时发生的错误进行分类的函数。
+    ///
+    /// 默认根据 [`std::io::Error`] 的类别和系统错误码分类，`ECONNABORTED` 等单个连接的错误为
+    /// [`AcceptErrorKind::Connection`]，`EMFILE`、`ENFILE` 等资源不足的错误为
+    /// [`AcceptErrorKind::Resource`]，其他错误为 [`AcceptErrorKind::Fatal`]。
+    pub fn classify_accept_error<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&L::Error) -> AcceptErrorKind + Send + Sync + 'static,
+    {
+        self.accept_errors.classify = Some(Box::new(f));
+        self
+    }
+
+    /// 设置接收连接时发生错误后调用的函数，参数中包含错误的类别。
+    ///
+    /// 默认在启用 `tracing` 功能时记录可恢复的错误，致命错误由 [`Server::run`] 返回。
+    pub fn on_accept_error<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&L::Error, AcceptErrorKind) + Send + Sync + 'static,
+    {
+        self.accept_errors.report = Some(Box::new(f));
+        self
+    }
+
+    /// 设置记录连接指标的 [`Metrics`]，记录打开的连接数和正在优雅关闭的连接数。
+    ///
+    /// [`Metrics`]: crate::metrics::Metrics
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&mut self, metrics: crate::metrics::Metrics) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     /// See [`Http1Builder::half_close`]。
@@ -271,10 +448,30 @@ where
     {
         let mut signal = std::pin::pin!(signal);
 
-        let service = compat::service_to_hyper(service);
+        let responder = self.error_responder.clone();
+        let service = match self.concurrency_limit {
+            Some(limit) => compat::service_to_hyper(limit.transform(service), responder),
+            None => compat::service_to_hyper(service, responder),
+        };
+        let service = service.min_body_rate(self.connection_limits.min_body_rate);
+        let connection_permits = self
+            .max_connections
+            .map(|max| Arc::new(Semaphore::new(max)));
         let graceful_shutdown = GracefulShutdown::new();
+        #[cfg(feature = "metrics")]
+        let graceful_shutdown = graceful_shutdown.with_metrics(self.metrics.clone());
 
         loop {
+            let permit = match &connection_permits {
+                Some(permits) => tokio::select! {
+                    _ = signal.as_mut() => break,
+                    permit = permits.clone().acquire_owned() => {
+                        Some(permit.expect("semaphore is never closed"))
+                    }
+                },
+                None => None,
+            };
+
             tokio::select! {
                 _ = signal.as_mut() => {
                     // 停止接收新连接
@@ -283,23 +480,59 @@ where
                 incoming = self.listener.accept() => {
                     let (conn, addr) = match incoming {
                         Ok(value) => value,
-                        Err(e) => return Err(RunError::Listener(e, graceful_shutdown)),
+                        Err(e) => {
+                            let kind = self.accept_errors.classify(&e);
+                            self.accept_errors.report(&e, kind);
+                            match kind {
+                                AcceptErrorKind::Connection => continue,
+                                AcceptErrorKind::Resource => {
+                                    tokio::select! {
+                                        _ = signal.as_mut() => break,
+                                        _ = tokio::time::sleep(self.accept_backoff) => continue,
+                                    }
+                                }
+                                AcceptErrorKind::Fatal => {
+                                    return Err(RunError::Listener(e, graceful_shutdown));
+                                }
+                            }
+                        }
                     };
 
+                    let connection = Arc::new(Connection::new(self.connection_limits));
+
                     let service = service.clone();
-                    let service = hyper::service::service_fn(move |mut request| {
-                        request.extensions_mut().insert(addr.clone());
-                        let service = service.clone();
-                        async move { service.call(request).await }
+                    let service = hyper::service::service_fn({
+                        let connection = connection.clone();
+                        move |mut request| {
+                            L::insert_addr(&addr, request.extensions_mut());
+                            let request_guard = connection.request();
+                            let service = service.clone();
+                            async move {
+                                let _request_guard = request_guard;
+                                service.call(request).await
+                            }
+                        }
                     });
 
                     let monitor = graceful_shutdown.monitor();
+                    let closed = {
+                        let connection = connection.clone();
+                        async move { connection.closed().await }
+                    };
 
-                    let conn = TokioIo::new(conn);
+                    let conn = TokioIo::new(ConnectionIo::new(conn, connection));
                     let conn = self.builder.serve_connection_with_upgrades(conn, service).into_owned();
-                    let conn = monitor.watch(conn, |conn| conn.graceful_shutdown());
+                    let conn = monitor.watch(conn, closed, |conn| conn.graceful_shutdown());
+
+                    #[cfg(feature = "metrics")]
+                    let metrics = self.metrics.as_ref().map(crate::metrics::Metrics::connection);
 
-                    tokio::spawn(conn);
+                    tokio::spawn(async move {
+                        let _permit = permit;
+                        #[cfg(feature = "metrics")]
+                        let _metrics = metrics;
+                        conn.await
+                    });
                 }
             }
         }
@@ -326,3 +559,307 @@ impl<E: std::fmt::Display> std::fmt::Display for RunError<E> {
 }
 
 impl<E: std::error::Error> std::error::Error for RunError<E> {}
+
+#[cfg(all(test, feature = "http1"))]
+mod tests {
+    use std::time::Duration;
+
+    use boluo_core::handler::handler_fn;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+    use tokio::sync::{mpsc, oneshot};
+    use tokio::time::Instant;
+
+    use super::{AcceptErrorKind, RunError, Server};
+    use crate::listener::Listener;
+
+    /// 从通道接收连接的监听器。
+    struct ChannelListener(mpsc::UnboundedReceiver<std::io::Result<DuplexStream>>);
+
+    impl Listener for ChannelListener {
+        type IO = DuplexStream;
+        type Addr = ();
+        type Error = std::io::Error;
+
+        async fn accept(&mut self) -> std::io::Result<(Self::IO, Self::Addr)> {
+            match self.0.recv().await {
+                Some(conn) => conn.map(|conn| (conn, ())),
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    fn listener() -> (
+        ChannelListener,
+        mpsc::UnboundedSender<std::io::Result<DuplexStream>>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (ChannelListener(rx), tx)
+    }
+
+    fn connect(tx: &mpsc::UnboundedSender<std::io::Result<DuplexStream>>) -> DuplexStream {
+        let (client, server) = tokio::io::duplex(4096);
+        tx.send(Ok(server)).unwrap();
+        client
+    }
+
+    fn spawn(mut server: Server<ChannelListener>) {
+        tokio::spawn(async move { server.run(handler_fn(|| async { "ok" })).await });
+    }
+
+    /// 发送请求并读取完整的响应。
+    async fn request(client: &mut DuplexStream) -> String {
+        client
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\nok") {
+            let mut buf = [0; 1024];
+            let n = client.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed before the response was complete");
+            response.extend_from_slice(&buf[..n]);
+        }
+        String::from_utf8(response).unwrap()
+    }
+
+    /// 等待服务器关闭连接，返回等待的时间。
+    async fn closed(client: &mut DuplexStream) -> Duration {
+        let start = Instant::now();
+        let mut buf = [0; 1024];
+        assert_eq!(client.read(&mut buf).await.unwrap(), 0);
+        start.elapsed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn idle_timeout() {
+        let (listener, tx) = listener();
+        let mut server = Server::new(listener);
+        server.idle_timeout(Duration::from_secs(1));
+        spawn(server);
+
+        let mut client = connect(&tx);
+        request(&mut client).await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        // 读写数据会推迟空闲超时。
+        request(&mut client).await;
+        let elapsed = closed(&mut client).await;
+        assert!(elapsed >= Duration::from_secs(1) && elapsed < Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_connection_lifetime() {
+        let (listener, tx) = listener();
+        let mut server = Server::new(listener);
+        server.max_connection_lifetime(Duration::from_secs(3));
+        spawn(server);
+
+        let start = Instant::now();
+        let mut client = connect(&tx);
+        for _ in 0..2 {
+            request(&mut client).await;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+        closed(&mut client).await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_secs(3) && elapsed < Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_requests_per_connection() {
+        let (listener, tx) = listener();
+        let mut server = Server::new(listener);
+        server.max_requests_per_connection(2);
+        spawn(server);
+
+        let mut client = connect(&tx);
+        request(&mut client).await;
+        // 第二个请求的响应完成后立即关闭连接。
+        request(&mut client).await;
+        assert_eq!(closed(&mut client).await, Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn max_connections() {
+        let (listener, tx) = listener();
+        let mut server = Server::new(listener);
+        server.max_connections(1);
+        spawn(server);
+
+        let mut first = connect(&tx);
+        request(&mut first).await;
+
+        // 连接数达到上限，第二个连接在第一个连接关闭前不会被接收。
+        let mut second = connect(&tx);
+        let pending = tokio::time::timeout(Duration::from_secs(10), request(&mut second)).await;
+        assert!(pending.is_err());
+
+        drop(first);
+        request(&mut second).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrency_limit() {
+        use std::sync::Arc;
+
+        use tokio::sync::Semaphore;
+
+        use crate::middleware::ConcurrencyLimit;
+
+        let (listener, tx) = listener();
+        let gate = Arc::new(Semaphore::new(0));
+        let mut server = Server::new(listener);
+        server.concurrency_limit(ConcurrencyLimit::new(1).queue(0));
+        tokio::spawn({
+            let gate = gate.clone();
+            async move {
+                server
+                    .run(handler_fn(move || {
+                        let gate = gate.clone();
+                        async move {
+                            let _ = gate.acquire().await.unwrap();
+                            "ok"
+                        }
+                    }))
+                    .await
+            }
+        });
+
+        let mut first = connect(&tx);
+        let pending = tokio::spawn(async move { request(&mut first).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // 限制作用于所有连接，另一个连接上的请求被拒绝。
+        let mut second = connect(&tx);
+        second
+            .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0; 1024];
+        let n = second.read(&mut buf).await.unwrap();
+        assert!(buf[..n].starts_with(b"HTTP/1.1 503"));
+
+        gate.add_permits(1);
+        assert!(pending.await.unwrap().starts_with("HTTP/1.1 200"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn graceful_shutdown_connections() {
+        let (listener, tx) = listener();
+        let (signal_tx, signal_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            Server::new(listener)
+                .run_with_graceful_shutdown(handler_fn(|| async { "ok" }), async {
+                    let _ = signal_rx.await;
+                })
+                .await
+                .unwrap()
+        });
+
+        let mut first = connect(&tx);
+        let mut second = connect(&tx);
+        request(&mut first).await;
+        request(&mut second).await;
+
+        signal_tx.send(()).unwrap();
+        let graceful_shutdown = server.await.unwrap();
+        assert_eq!(graceful_shutdown.connections(), 2);
+
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(graceful_shutdown.connections(), 1);
+
+        drop(second);
+        graceful_shutdown
+            .shutdown(Duration::from_secs(1))
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test(start_paused = true)]
+    async fn draining_connections_metrics() {
+        let draining = |metrics: &crate::metrics::Metrics| {
+            metrics
+                .render()
+                .lines()
+                .find_map(|line| line.strip_prefix("http_server_draining_connections "))
+                .unwrap()
+                .to_owned()
+        };
+
+        let metrics = crate::metrics::Metrics::new();
+        let (listener, tx) = listener();
+        let (signal_tx, signal_rx) = oneshot::channel::<()>();
+        let server = tokio::spawn({
+            let metrics = metrics.clone();
+            async move {
+                Server::new(listener)
+                    .metrics(metrics)
+                    .run_with_graceful_shutdown(
+                        handler_fn(|| async {
+                            tokio::time::sleep(Duration::from_secs(1)).await;
+                            "ok"
+                        }),
+                        async {
+                            let _ = signal_rx.await;
+                        },
+                    )
+                    .await
+                    .unwrap()
+            }
+        });
+
+        let mut client = connect(&tx);
+        let pending = tokio::spawn(async move { request(&mut client).await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // 服务器停止接收连接，但连接还没有收到关闭信号。
+        signal_tx.send(()).unwrap();
+        let graceful_shutdown = server.await.unwrap();
+        assert_eq!(draining(&metrics), "0");
+
+        let shutdown = tokio::spawn(graceful_shutdown.shutdown(Duration::from_secs(10)));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(draining(&metrics), "1");
+
+        pending.await.unwrap();
+        shutdown.await.unwrap().unwrap();
+        assert_eq!(draining(&metrics), "0");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accept_errors() {
+        use std::io::{Error, ErrorKind};
+        use std::sync::{Arc, Mutex};
+
+        let (listener, tx) = listener();
+        let kinds = Arc::new(Mutex::new(Vec::new()));
+        let mut server = Server::new(listener);
+        server.on_accept_error({
+            let kinds = kinds.clone();
+            move |_, kind| kinds.lock().unwrap().push(kind)
+        });
+        let server = tokio::spawn(async move { server.run(handler_fn(|| async { "ok" })).await });
+
+        // 单个连接的错误和资源不足的错误不会停止服务器。
+        tx.send(Err(Error::from(ErrorKind::ConnectionAborted)))
+            .unwrap();
+        tx.send(Err(Error::from(ErrorKind::OutOfMemory))).unwrap();
+        let mut client = connect(&tx);
+        request(&mut client).await;
+
+        tx.send(Err(Error::from(ErrorKind::InvalidInput))).unwrap();
+        let Err(RunError::Listener(error, _)) = server.await.unwrap() else {
+            panic!("fatal accept error should stop the server");
+        };
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert_eq!(
+            *kinds.lock().unwrap(),
+            [
+                AcceptErrorKind::Connection,
+                AcceptErrorKind::Resource,
+                AcceptErrorKind::Fatal
+            ]
+        );
+    }
+}
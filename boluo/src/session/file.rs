@@ -0,0 +1,159 @@
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use boluo_core::BoxError;
+use tokio::io::AsyncWriteExt;
+
+use super::{SessionRecord, SessionStore};
+
+/// 将会话记录以 JSON 格式保存在目录中的存储，每个会话对应一个文件。
+///
+/// 过期的记录会在加载时删除。在 Unix 平台上，新建的目录和文件只允许当前用户访问，
+/// 权限分别为 `0o700` 和 `0o600`。
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// 使用指定目录创建 [`FileStore`]，目录不存在时会在首次保存会话时创建。
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 获取保存会话记录的目录。
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 获取会话记录的文件路径，标识符包含字母和数字以外的字符时返回 `None`，防止访问目录以外的文件。
+    fn path(&self, id: &str) -> Option<PathBuf> {
+        (!id.is_empty() && id.bytes().all(|b| b.is_ascii_alphanumeric()))
+            .then(|| self.dir.join(format!("{id}.json")))
+    }
+}
+
+impl SessionStore for FileStore {
+    async fn load(&self, id: &str) -> Result<Option<SessionRecord>, BoxError> {
+        let Some(path) = self.path(id) else {
+            return Ok(None);
+        };
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let record = serde_json::from_slice::<SessionRecord>(&bytes)?;
+        if record.is_expired() {
+            self.delete(id).await?;
+            return Ok(None);
+        }
+        Ok(Some(record))
+    }
+
+    async fn save(&self, record: &SessionRecord) -> Result<(), BoxError> {
+        let path = self
+            .path(&record.id)
+            .ok_or_else(|| format!("invalid session id `{}`", record.id))?;
+        let bytes = serde_json::to_vec(record)?;
+        create_dir(&self.dir).await?;
+
+        // 先写入临时文件再重命名，避免并发读取到不完整的文件。每次写入使用不同的临时文件，
+        // 同一会话的并发保存不会互相覆盖临时文件。
+        let tmp = self.dir.join(format!(
+            "{}.{}.tmp",
+            record.id,
+            uuid::Uuid::new_v4().simple()
+        ));
+        let result = match write_file(&tmp, &bytes).await {
+            Ok(()) => tokio::fs::rename(&tmp, &path).await,
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        result.map_err(Into::into)
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), BoxError> {
+        let Some(path) = self.path(id) else {
+            return Ok(());
+        };
+        match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+async fn create_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = tokio::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(dir).await
+}
+
+async fn write_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path).await?;
+    file.write_all(bytes).await?;
+    file.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, SystemTime};
+
+    use super::{FileStore, SessionRecord, SessionStore};
+
+    #[tokio::test]
+    async fn save_load_delete() {
+        let dir = std::env::temp_dir().join(format!("boluo-session-{}", std::process::id()));
+        let store = FileStore::new(&dir);
+        let record = SessionRecord {
+            id: "abc123".to_owned(),
+            data: HashMap::from([("user".to_owned(), serde_json::json!("alice"))]),
+            expires_at: SystemTime::now() + Duration::from_secs(60),
+        };
+
+        store.save(&record).await.unwrap();
+        assert_eq!(store.load("abc123").await.unwrap(), Some(record.clone()));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = |path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(dir.clone()), 0o700);
+            assert_eq!(mode(dir.join("abc123.json")), 0o600);
+        }
+
+        // 并发保存同一会话不会因为共用临时文件而失败，也不会遗留临时文件。
+        let saves = (0..8).map(|_| store.save(&record));
+        for result in futures_util::future::join_all(saves).await {
+            result.unwrap();
+        }
+        let files = std::fs::read_dir(&dir).unwrap().count();
+        assert_eq!(files, 1);
+        assert_eq!(store.load("../abc123").await.unwrap(), None);
+
+        store.delete("abc123").await.unwrap();
+        assert_eq!(store.load("abc123").await.unwrap(), None);
+
+        let expired = SessionRecord {
+            id: "expired".to_owned(),
+            data: HashMap::new(),
+            expires_at: SystemTime::now() - Duration::from_secs(1),
+        };
+        store.save(&expired).await.unwrap();
+        assert_eq!(store.load("expired").await.unwrap(), None);
+        assert!(!dir.join("expired.json").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,251 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use boluo_core::BoxError;
+use boluo_core::http::HeaderValue;
+use boluo_core::http::header::{self, HeaderMap};
+use boluo_core::middleware::Middleware;
+use boluo_core::request::Request;
+use boluo_core::response::{IntoResponse, Response};
+use boluo_core::service::Service;
+
+use super::{Session, SessionError, SessionRecord, SessionStore};
+use crate::cookie::{Cookie, SameSite, time};
+
+/// 默认的会话有效期。
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 会话标识符的最大长度，超过此长度的会话标识符会被忽略。
+const MAX_ID_LEN: usize = 64;
+
+/// 管理会话的中间件。
+///
+/// 中间件从 Cookie 中读取会话标识符，从存储中加载会话并以 [`Session`] 的形式保存在请求扩展中。
+/// 内部服务返回响应后，修改过的会话会被写入存储，并在响应中设置保存会话标识符的 Cookie。
+/// 内部服务返回错误时不会保存会话。
+///
+/// 默认的 Cookie 名称为 `id`，设置了 `Path=/`、`HttpOnly`、`Secure` 和 `SameSite=Lax` 属性，
+/// 会话有效期为 24 小时。
+#[derive(Debug)]
+pub struct SessionManager<T> {
+    store: Arc<T>,
+    cookie_name: Cow<'static, str>,
+    path: Cow<'static, str>,
+    domain: Option<Cow<'static, str>>,
+    secure: bool,
+    same_site: SameSite,
+    max_age: Duration,
+}
+
+impl<T> Clone for SessionManager<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            cookie_name: self.cookie_name.clone(),
+            path: self.path.clone(),
+            domain: self.domain.clone(),
+            secure: self.secure,
+            same_site: self.same_site,
+            max_age: self.max_age,
+        }
+    }
+}
+
+impl<T: SessionStore> SessionManager<T> {
+    /// 使用指定的存储创建 [`SessionManager`]。
+    pub fn new(store: T) -> Self {
+        Self {
+            store: Arc::new(store),
+            cookie_name: Cow::Borrowed("id"),
+            path: Cow::Borrowed("/"),
+            domain: None,
+            secure: true,
+            same_site: SameSite::Lax,
+            max_age: DEFAULT_MAX_AGE,
+        }
+    }
+
+    /// 设置保存会话标识符的 Cookie 名称。
+    pub fn cookie_name(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    /// 设置 Cookie 的 `Path` 属性。
+    pub fn path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// 设置 Cookie 的 `Domain` 属性。
+    pub fn domain(mut self, domain: impl Into<Cow<'static, str>>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// 设置 Cookie 是否只能通过 HTTPS 发送。
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// 设置 Cookie 的 `SameSite` 属性。
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = same_site;
+        self
+    }
+
+    /// 设置会话的有效期，会话每次修改后重新计算过期时间。
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    async fn load(&self, headers: &HeaderMap) -> Result<Option<SessionRecord>, SessionError> {
+        let Some(id) = self.session_id(headers) else {
+            return Ok(None);
+        };
+        let record = self.store.load(&id).await.map_err(SessionError::Store)?;
+        Ok(record.filter(|record| !record.is_expired()))
+    }
+
+    fn session_id(&self, headers: &HeaderMap) -> Option<String> {
+        crate::cookie::parse_cookies(headers)
+            .get(&self.cookie_name)
+            .map(|cookie| cookie.value())
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_ID_LEN
+                    && id.bytes().all(|b| b.is_ascii_alphanumeric())
+            })
+            .map(ToOwned::to_owned)
+    }
+
+    async fn save(
+        &self,
+        session: &Session,
+        had_cookie: bool,
+        headers: &mut HeaderMap,
+    ) -> Result<(), BoxError> {
+        let (id, stale_id, data, max_age) = {
+            let mut state = session.state();
+            if !state.changed && state.stale_id.is_none() {
+                return Ok(());
+            }
+            state.changed = false;
+            (
+                state.id.take(),
+                state.stale_id.take(),
+                std::mem::take(&mut state.data),
+                state.max_age.unwrap_or(self.max_age),
+            )
+        };
+
+        if let Some(stale_id) = stale_id {
+            self.store
+                .delete(&stale_id)
+                .await
+                .map_err(SessionError::Store)?;
+        }
+
+        if data.is_empty() {
+            if let Some(id) = id {
+                self.store.delete(&id).await.map_err(SessionError::Store)?;
+            }
+            if had_cookie {
+                let mut cookie = self.cookie(String::new(), Duration::ZERO);
+                cookie.make_removal();
+                set_cookie(headers, cookie)?;
+            }
+            return Ok(());
+        }
+
+        let record = SessionRecord {
+            id: id.unwrap_or_else(generate_id),
+            data,
+            expires_at: SystemTime::now() + max_age,
+        };
+        self.store
+            .save(&record)
+            .await
+            .map_err(SessionError::Store)?;
+        set_cookie(headers, self.cookie(record.id, max_age))
+    }
+
+    fn cookie(&self, id: String, max_age: Duration) -> Cookie<'static> {
+        let mut cookie = Cookie::build((self.cookie_name.clone(), id))
+            .path(self.path.clone())
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(self.same_site)
+            .max_age(time::Duration::try_from(max_age).unwrap_or(time::Duration::MAX))
+            .build();
+        if let Some(domain) = &self.domain {
+            cookie.set_domain(domain.clone());
+        }
+        cookie
+    }
+}
+
+impl<T, S> Middleware<S> for SessionManager<T>
+where
+    T: SessionStore,
+{
+    type Service = SessionManagerService<T, S>;
+
+    fn transform(self, service: S) -> Self::Service {
+        SessionManagerService {
+            service,
+            manager: self,
+        }
+    }
+}
+
+/// 中间件 [`SessionManager`] 返回的服务。
+#[derive(Debug, Clone)]
+pub struct SessionManagerService<T, S> {
+    service: S,
+    manager: SessionManager<T>,
+}
+
+impl<T, S> Service<Request> for SessionManagerService<T, S>
+where
+    T: SessionStore,
+    S: Service<Request>,
+    S::Response: IntoResponse,
+    S::Error: Into<BoxError>,
+{
+    type Response = Response;
+    type Error = BoxError;
+
+    async fn call(&self, mut request: Request) -> Result<Self::Response, Self::Error> {
+        let had_cookie = self.manager.session_id(request.headers()).is_some();
+        let session = Session::new(self.manager.load(request.headers()).await?);
+        request.extensions_mut().insert(session.clone());
+
+        let mut response = self
+            .service
+            .call(request)
+            .await
+            .map_err(Into::into)?
+            .into_response()
+            .map_err(Into::into)?;
+
+        // 响应生成后处理程序不再修改会话，此时写入存储。
+        self.manager
+            .save(&session, had_cookie, response.headers_mut())
+            .await?;
+        Ok(response)
+    }
+}
+
+fn set_cookie(headers: &mut HeaderMap, cookie: Cookie<'static>) -> Result<(), BoxError> {
+    let value = HeaderValue::try_from(cookie.to_string())?;
+    headers.append(header::SET_COOKIE, value);
+    Ok(())
+}
+
+fn generate_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
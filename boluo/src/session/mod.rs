@@ -0,0 +1,322 @@
+//! 基于 Cookie 的会话。
+//!
+//! 中间件 [`SessionManager`] 从 Cookie 中读取会话标识符并从 [`SessionStore`] 加载会话，
+//! 处理程序通过提取器 [`Session`] 读写会话数据。服务返回响应后，修改过的会话会被写入存储，
+//! 并通过 `Set-Cookie` 标头将会话标识符发送给客户端。
+//!
+//! # 例子
+//!
+//! ```
+//! use boluo::BoxError;
+//! use boluo::route::Router;
+//! use boluo::service::ServiceExt;
+//! use boluo::session::{MemoryStore, Session, SessionManager};
+//!
+//! #[boluo::route("/login", method = "POST")]
+//! async fn login(session: Session) -> Result<&'static str, BoxError> {
+//!     // 登录后轮换会话标识符，防止会话固定攻击。
+//!     session.cycle_id();
+//!     session.insert("user_id", 42_u64)?;
+//!     Ok("ok")
+//! }
+//!
+//! #[boluo::route("/me", method = "GET")]
+//! async fn me(session: Session) -> Result<String, BoxError> {
+//!     Ok(match session.get::<u64>("user_id")? {
+//!         Some(user_id) => format!("user {user_id}"),
+//!         None => "anonymous".to_owned(),
+//!     })
+//! }
+//!
+//! #[boluo::route("/logout", method = "POST")]
+//! async fn logout(session: Session) -> &'static str {
+//!     session.purge();
+//!     "ok"
+//! }
+//!
+//! let router = Router::new()
+//!     .mount(login)
+//!     .mount(me)
+//!     .mount(logout)
+//!     .with(SessionManager::new(MemoryStore::new()));
+//! ```
+
+mod file;
+mod manager;
+mod store;
+
+pub use file::FileStore;
+pub use manager::{SessionManager, SessionManagerService};
+pub use store::{MemoryStore, SessionRecord, SessionStore};
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use boluo_core::BoxError;
+use boluo_core::extract::{FromRequest, OptionalFromRequest};
+use boluo_core::http::StatusCode;
+use boluo_core::request::Request;
+use boluo_core::response::ErrorResponse;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::extract::ExtensionError;
+
+/// 会话。
+///
+/// 由中间件 [`SessionManager`] 保存在请求扩展中，可以作为提取器使用。克隆的实例共享同一份会话数据。
+///
+/// 会话数据使用 JSON 格式保存，可以读写任何实现了 [`Serialize`] 和 [`DeserializeOwned`] 的类型。
+/// 修改会话时会根据会话的有效期更新过期时间。
+#[derive(Debug, Clone)]
+pub struct Session {
+    state: Arc<Mutex<SessionState>>,
+}
+
+#[derive(Debug, Default)]
+struct SessionState {
+    /// 当前会话标识符，新会话或轮换标识符后为 `None`，保存时生成新的标识符。
+    id: Option<String>,
+    /// 需要从存储中删除的会话标识符。
+    stale_id: Option<String>,
+    data: HashMap<String, serde_json::Value>,
+    expires_at: Option<SystemTime>,
+    max_age: Option<Duration>,
+    changed: bool,
+}
+
+impl Session {
+    fn new(record: Option<SessionRecord>) -> Self {
+        let state = match record {
+            Some(record) => SessionState {
+                id: Some(record.id),
+                data: record.data,
+                expires_at: Some(record.expires_at),
+                ..Default::default()
+            },
+            None => Default::default(),
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 获取会话标识符，新会话或轮换标识符后在保存前返回 `None`。
+    pub fn id(&self) -> Option<String> {
+        self.state().id.clone()
+    }
+
+    /// 获取会话的过期时间，新会话返回 `None`。
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.state().expires_at
+    }
+
+    /// 设置会话的有效期，覆盖 [`SessionManager::max_age`] 的设置。
+    pub fn set_expiry(&self, max_age: Duration) {
+        let mut state = self.state();
+        state.max_age = Some(max_age);
+        state.changed = true;
+    }
+
+    /// 获取指定键的值。
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SessionError> {
+        self.state()
+            .data
+            .get(key)
+            .map(|value| T::deserialize(value).map_err(SessionError::Deserialize))
+            .transpose()
+    }
+
+    /// 插入键值对，已存在相同的键时覆盖旧值。
+    pub fn insert<T: Serialize>(
+        &self,
+        key: impl Into<String>,
+        value: T,
+    ) -> Result<(), SessionError> {
+        let value = serde_json::to_value(value).map_err(SessionError::Serialize)?;
+        let mut state = self.state();
+        state.data.insert(key.into(), value);
+        state.changed = true;
+        Ok(())
+    }
+
+    /// 删除指定键的值，并返回被删除的值。
+    pub fn remove<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, SessionError> {
+        let mut state = self.state();
+        let Some(value) = state.data.remove(key) else {
+            return Ok(None);
+        };
+        state.changed = true;
+        serde_json::from_value(value)
+            .map(Some)
+            .map_err(SessionError::Deserialize)
+    }
+
+    /// 判断会话是否包含指定的键。
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.state().data.contains_key(key)
+    }
+
+    /// 清空会话数据，会话标识符保持不变。
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.data.clear();
+        state.changed = true;
+    }
+
+    /// 轮换会话标识符，会话数据保持不变。
+    ///
+    /// 用户登录或权限变化时应该轮换会话标识符，防止会话固定攻击。旧的会话记录会从存储中删除。
+    pub fn cycle_id(&self) {
+        let mut state = self.state();
+        if let Some(id) = state.id.take() {
+            state.stale_id.get_or_insert(id);
+        }
+        state.changed = true;
+    }
+
+    /// 销毁会话，删除会话数据和存储中的会话记录，并使客户端的 Cookie 过期。
+    ///
+    /// 销毁后插入的数据会保存到使用新标识符的会话中。
+    pub fn purge(&self) {
+        self.cycle_id();
+        self.clear();
+    }
+}
+
+impl FromRequest for Session {
+    type Error = ExtensionError;
+
+    async fn from_request(request: &mut Request) -> Result<Self, Self::Error> {
+        let opt = Option::<Session>::from_request(request)
+            .await
+            .map_err(|e| match e {})?;
+        opt.ok_or_else(|| ExtensionError::MissingExtension {
+            name: std::any::type_name::<Session>(),
+        })
+    }
+}
+
+impl OptionalFromRequest for Session {
+    type Error = Infallible;
+
+    async fn from_request(request: &mut Request) -> Result<Option<Self>, Self::Error> {
+        Ok(request.extensions().get::<Session>().cloned())
+    }
+}
+
+/// 会话错误。
+#[derive(Debug)]
+pub enum SessionError {
+    /// 序列化会话数据失败。
+    Serialize(serde_json::Error),
+    /// 反序列化会话数据失败。
+    Deserialize(serde_json::Error),
+    /// 读写会话存储失败。
+    Store(BoxError),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::Serialize(e) => write!(f, "failed to serialize session value ({e})"),
+            SessionError::Deserialize(e) => {
+                write!(f, "failed to deserialize session value ({e})")
+            }
+            SessionError::Store(e) => write!(f, "session store error ({e})"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl ErrorResponse for SessionError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{Uri, header};
+    use boluo_core::middleware::Middleware;
+    use boluo_core::request::Request;
+    use boluo_core::response::Response;
+    use boluo_core::service::Service;
+
+    use super::{MemoryStore, Session, SessionManager, SessionStore};
+
+    fn session_cookie(response: &Response) -> Option<String> {
+        let value = response
+            .headers()
+            .get(header::SET_COOKIE)?
+            .to_str()
+            .unwrap();
+        let (pair, _) = value.split_once(';').unwrap_or((value, ""));
+        Some(pair.to_owned())
+    }
+
+    async fn call<S: Service<Request, Response = Response>>(
+        service: &S,
+        path: &str,
+        cookie: Option<&str>,
+    ) -> Response
+    where
+        S::Error: std::fmt::Debug,
+    {
+        let mut builder = Request::builder().uri(path);
+        if let Some(cookie) = cookie {
+            builder = builder.header(header::COOKIE, cookie);
+        }
+        service
+            .call(builder.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn login_and_logout() {
+        let store = MemoryStore::new();
+        let service = SessionManager::new(store.clone()).transform(handler_fn(
+            |uri: Uri, session: Session| async move {
+                match uri.path() {
+                    "/login" => {
+                        session.cycle_id();
+                        session.insert("user", "alice").unwrap();
+                    }
+                    "/logout" => session.purge(),
+                    _ => {}
+                }
+                session.get::<String>("user").unwrap().unwrap_or_default()
+            },
+        ));
+
+        let response = call(&service, "/login", None).await;
+        let cookie = session_cookie(&response).unwrap();
+        let id = cookie.strip_prefix("id=").unwrap().to_owned();
+        assert!(store.load(&id).await.unwrap().is_some());
+
+        let response = call(&service, "/", Some(&cookie)).await;
+        assert!(session_cookie(&response).is_none());
+        assert_eq!(response.into_body().to_bytes().await.unwrap(), "alice");
+
+        // 再次登录时轮换会话标识符，删除旧的会话记录。
+        let response = call(&service, "/login", Some(&cookie)).await;
+        let new_cookie = session_cookie(&response).unwrap();
+        assert_ne!(new_cookie, cookie);
+        assert!(store.load(&id).await.unwrap().is_none());
+
+        let response = call(&service, "/logout", Some(&new_cookie)).await;
+        assert_eq!(session_cookie(&response).as_deref(), Some("id="));
+        let new_id = new_cookie.strip_prefix("id=").unwrap();
+        assert!(store.load(new_id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use boluo_core::BoxError;
+use serde::{Deserialize, Serialize};
+
+/// 存储中保存的会话记录。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// 会话标识符。
+    pub id: String,
+    /// 会话数据。
+    pub data: HashMap<String, serde_json::Value>,
+    /// 会话的过期时间。
+    pub expires_at: SystemTime,
+}
+
+impl SessionRecord {
+    /// 判断会话是否已经过期。
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= SystemTime::now()
+    }
+}
+
+/// 保存会话记录的存储。
+///
+/// [`SessionManager`] 在服务返回响应后才会写入存储，加载的会话记录已过期时会被视为不存在。
+///
+/// [`SessionManager`]: super::SessionManager
+pub trait SessionStore: Send + Sync {
+    /// 加载指定标识符的会话记录，记录不存在时返回 `None`。
+    fn load(
+        &self,
+        id: &str,
+    ) -> impl Future<Output = Result<Option<SessionRecord>, BoxError>> + Send;
+
+    /// 保存会话记录，已存在相同标识符的记录时覆盖。
+    fn save(&self, record: &SessionRecord) -> impl Future<Output = Result<(), BoxError>> + Send;
+
+    /// 删除指定标识符的会话记录，记录不存在时不返回错误。
+    fn delete(&self, id: &str) -> impl Future<Output = Result<(), BoxError>> + Send;
+}
+
+/// 将会话记录保存在内存中的存储，克隆的实例共享同一份数据。
+///
+/// 过期的记录会在加载时删除，也可以调用 [`MemoryStore::remove_expired`] 定期清理。
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    records: Arc<Mutex<HashMap<String, SessionRecord>>>,
+}
+
+impl MemoryStore {
+    /// 创建空的 [`MemoryStore`]。
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// 删除所有过期的会话记录。
+    pub fn remove_expired(&self) {
+        self.records().retain(|_, record| !record.is_expired());
+    }
+
+    fn records(&self) -> std::sync::MutexGuard<'_, HashMap<String, SessionRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SessionStore for MemoryStore {
+    async fn load(&self, id: &str) -> Result<Option<SessionRecord>, BoxError> {
+        let mut records = self.records();
+        match records.get(id) {
+            Some(record) if record.is_expired() => {
+                records.remove(id);
+                Ok(None)
+            }
+            record => Ok(record.cloned()),
+        }
+    }
+
+    async fn save(&self, record: &SessionRecord) -> Result<(), BoxError> {
+        self.records().insert(record.id.clone(), record.clone());
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), BoxError> {
+        self.records().remove(id);
+        Ok(())
+    }
+}
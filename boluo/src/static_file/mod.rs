@@ -9,7 +9,7 @@ use std::path::{Path, PathBuf};
 use boluo_core::body::Body;
 use boluo_core::http::StatusCode;
 use boluo_core::request::Request;
-use boluo_core::response::{IntoResponse, Response};
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
 use boluo_core::service::Service;
 use headers::{
     AcceptRanges, ContentLength, ContentRange, ContentType, HeaderMapExt, LastModified, Range,
@@ -202,6 +202,15 @@ impl std::fmt::Display for ServeFileError {
 
 impl std::error::Error for ServeFileError {}
 
+impl ErrorResponse for ServeFileError {
+    fn status(&self) -> StatusCode {
+        match self {
+            ServeFileError::NotFound => StatusCode::NOT_FOUND,
+            ServeFileError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 impl ServeFileError {
     pub(crate) fn from_io(error: std::io::Error) -> Self {
         match error.kind() {
@@ -0,0 +1,81 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use boluo_core::http::header::{self, HeaderMap, HeaderValue};
+
+/// 客户端保存的 Cookie。
+#[derive(Default, Clone)]
+pub(super) struct CookieStore {
+    cookies: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl CookieStore {
+    pub(super) fn get(&self, name: &str) -> Option<String> {
+        self.cookies.lock().unwrap().get(name).cloned()
+    }
+
+    pub(super) fn insert(&self, name: String, value: String) {
+        self.cookies.lock().unwrap().insert(name, value);
+    }
+
+    pub(super) fn remove(&self, name: &str) {
+        self.cookies.lock().unwrap().remove(name);
+    }
+
+    pub(super) fn clear(&self) {
+        self.cookies.lock().unwrap().clear();
+    }
+
+    /// 生成请求的 `Cookie` 标头。
+    pub(super) fn header_value(&self) -> Option<HeaderValue> {
+        let cookies = self.cookies.lock().unwrap();
+        if cookies.is_empty() {
+            return None;
+        }
+        let value = cookies
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::try_from(value).ok()
+    }
+
+    /// 根据响应的 `Set-Cookie` 标头更新 Cookie。
+    pub(super) fn update(&self, headers: &HeaderMap) {
+        for value in headers.get_all(header::SET_COOKIE) {
+            let Some((name, value, expired)) = value.to_str().ok().and_then(parse_set_cookie)
+            else {
+                continue;
+            };
+            if expired {
+                self.remove(&name);
+            } else {
+                self.insert(name, value);
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for CookieStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.cookies.lock().unwrap().iter())
+            .finish()
+    }
+}
+
+/// 解析 `Set-Cookie` 标头，返回名称、值以及 Cookie 是否已过期。
+fn parse_set_cookie(value: &str) -> Option<(String, String, bool)> {
+    let mut parts = value.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let expired = parts.any(|attr| {
+        let (key, value) = attr.split_once('=').unwrap_or((attr, ""));
+        key.trim().eq_ignore_ascii_case("max-age")
+            && value.trim().parse::<i64>().is_ok_and(|age| age <= 0)
+    });
+    Some((name.to_owned(), value.trim().to_owned(), expired))
+}
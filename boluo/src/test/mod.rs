@@ -0,0 +1,344 @@
+//! 在进程内测试服务的工具。
+//!
+//! [`TestClient`] 直接调用服务，不经过网络连接。服务返回的错误按照 [`Server`] 相同的方式转换为响应，
+//! `HEAD` 请求的响应主体同样会被丢弃。
+//!
+//! # 例子
+//!
+//! ```
+//! use boluo::http::StatusCode;
+//! use boluo::response::IntoResponse;
+//! use boluo::route::Router;
+//! use boluo::test::TestClient;
+//!
+//! #[boluo::route("/", method = "GET")]
+//! async fn hello() -> impl IntoResponse {
+//!     "Hello, World!"
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let client = TestClient::new(Router::new().mount(hello));
+//!
+//! let response = client.get("/").await;
+//! response.assert_status(StatusCode::OK);
+//! assert_eq!(response.text().await, "Hello, World!");
+//!
+//! let response = client.get("/missing").await;
+//! response.assert_status(StatusCode::NOT_FOUND);
+//! # }
+//! ```
+//!
+//! [`Server`]: crate::server::Server
+
+mod cookie;
+mod request;
+mod response;
+mod sse;
+mod upgrade;
+
+pub use request::TestRequest;
+pub use response::TestResponse;
+pub use sse::{SseEvent, SseStream};
+
+use boluo_core::BoxError;
+use boluo_core::http::{Method, StatusCode, Uri, header};
+use boluo_core::request::Request;
+use boluo_core::response::{ErrorResponder, IntoResponse, Response};
+use boluo_core::service::{ArcService, Service};
+
+use self::cookie::CookieStore;
+use self::upgrade::UpgradePipe;
+
+/// 在进程内调用服务的测试客户端。
+///
+/// 客户端会保存响应中 `Set-Cookie` 标头设置的 Cookie，并在之后的请求中携带。克隆的客户端共享 Cookie。
+#[derive(Clone)]
+pub struct TestClient {
+    service: ArcService<Request, Response, BoxError>,
+    error_responder: ErrorResponder,
+    cookies: CookieStore,
+}
+
+impl TestClient {
+    /// 使用给定服务创建测试客户端。
+    pub fn new<S>(service: S) -> Self
+    where
+        S: Service<Request> + 'static,
+        S::Response: IntoResponse,
+        S::Error: Into<BoxError>,
+    {
+        Self {
+            service: boluo_core::util::__into_arc_service(service),
+            error_responder: crate::response::error_responder(),
+            cookies: CookieStore::default(),
+        }
+    }
+
+    /// 设置将服务错误转换为响应的 [`ErrorResponder`]。
+    ///
+    /// 默认使用 [`error_responder`] 返回的实例。与服务器相同，客户端会将此实例添加到每个请求的扩展中。
+    ///
+    /// [`error_responder`]: crate::response::error_responder
+    pub fn error_responder(mut self, responder: ErrorResponder) -> Self {
+        self.error_responder = responder;
+        self
+    }
+
+    /// 使用给定方法和 URI 创建请求。
+    pub fn request<T>(&self, method: Method, uri: T) -> TestRequest
+    where
+        Uri: TryFrom<T>,
+        <Uri as TryFrom<T>>::Error: Into<boluo_core::http::Error>,
+    {
+        TestRequest::new(self.clone(), method, uri)
+    }
+
+    /// 创建 WebSocket 升级请求。
+    ///
+    /// 使用 [`TestResponse::into_websocket`] 获取客户端一侧的 WebSocket 连接。
+    #[cfg(feature = "ws")]
+    pub fn websocket<T>(&self, uri: T) -> TestRequest
+    where
+        Uri: TryFrom<T>,
+        <Uri as TryFrom<T>>::Error: Into<boluo_core::http::Error>,
+    {
+        self.get(uri)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_VERSION, "13")
+            .header(header::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+    }
+
+    /// 获取客户端保存的 Cookie。
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.cookies.get(name)
+    }
+
+    /// 设置之后的请求携带的 Cookie。
+    pub fn set_cookie(&self, name: impl Into<String>, value: impl Into<String>) {
+        self.cookies.insert(name.into(), value.into());
+    }
+
+    /// 删除客户端保存的 Cookie。
+    pub fn remove_cookie(&self, name: &str) {
+        self.cookies.remove(name);
+    }
+
+    /// 清空客户端保存的 Cookie。
+    pub fn clear_cookies(&self) {
+        self.cookies.clear();
+    }
+
+    /// 发送请求。
+    ///
+    /// 请求未设置 `Cookie` 标头时，会携带客户端保存的 Cookie。
+    /// 请求包含 `Upgrade` 标头时，可以通过内存管道完成协议升级。
+    pub async fn send(&self, mut request: Request) -> TestResponse {
+        if !request.headers().contains_key(header::COOKIE) {
+            if let Some(cookie) = self.cookies.header_value() {
+                request.headers_mut().insert(header::COOKIE, cookie);
+            }
+        }
+
+        let pipe = request
+            .headers()
+            .contains_key(header::UPGRADE)
+            .then(|| UpgradePipe::attach(&mut request));
+        let is_head = request.method() == Method::HEAD;
+        request
+            .extensions_mut()
+            .insert(self.error_responder.clone());
+
+        let (mut response, error) = match self.service.call(request).await {
+            Ok(response) => (response, None),
+            Err(error) => (self.error_responder.respond_always(&*error), Some(error)),
+        };
+        if is_head {
+            response = crate::response::strip_body(response);
+        }
+
+        self.cookies.update(response.headers());
+
+        let upgraded = pipe
+            .filter(|_| response.status() == StatusCode::SWITCHING_PROTOCOLS)
+            .map(UpgradePipe::upgrade);
+
+        TestResponse::new(response, error, upgraded)
+    }
+}
+
+macro_rules! impl_method_fn {
+    ($name:ident, $method:expr) => {
+        impl TestClient {
+            #[doc = concat!("创建 [`", stringify!($method), "`] 请求。")]
+            pub fn $name<T>(&self, uri: T) -> TestRequest
+            where
+                Uri: TryFrom<T>,
+                <Uri as TryFrom<T>>::Error: Into<boluo_core::http::Error>,
+            {
+                self.request($method, uri)
+            }
+        }
+    };
+}
+
+impl_method_fn!(delete, Method::DELETE);
+impl_method_fn!(get, Method::GET);
+impl_method_fn!(head, Method::HEAD);
+impl_method_fn!(options, Method::OPTIONS);
+impl_method_fn!(patch, Method::PATCH);
+impl_method_fn!(post, Method::POST);
+impl_method_fn!(put, Method::PUT);
+
+impl std::fmt::Debug for TestClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestClient")
+            .field("error_responder", &self.error_responder)
+            .field("cookies", &self.cookies)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::body::Body;
+    use boluo_core::handler::handler_fn;
+    use boluo_core::http::{StatusCode, header};
+    use boluo_core::response::{IntoResponse, Response};
+
+    use super::TestClient;
+    use crate::extract::Json;
+    use crate::route::{Router, get, post};
+
+    #[tokio::test]
+    async fn json_and_errors() {
+        let router = Router::new().route(
+            "/echo",
+            post(handler_fn(|Json(value): Json<serde_json::Value>| async {
+                Json(value)
+            })),
+        );
+        let client = TestClient::new(router);
+
+        let response = client
+            .post("/echo")
+            .json(&serde_json::json!({ "a": 1 }))
+            .await;
+        response.assert_status(StatusCode::OK);
+        let value: serde_json::Value = response.json().await;
+        assert_eq!(value, serde_json::json!({ "a": 1 }));
+
+        let response = client.post("/echo").body("{}").await;
+        response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(response.error().is_some());
+
+        let response = client.get("/echo").await;
+        response
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED)
+            .assert_header(header::ALLOW, "POST");
+    }
+
+    #[tokio::test]
+    async fn head_and_cookies() {
+        let router = Router::new()
+            .route(
+                "/login",
+                post(handler_fn(|| async {
+                    Response::builder()
+                        .header(header::SET_COOKIE, "session=abc; Path=/; HttpOnly")
+                        .body(Body::empty())
+                })),
+            )
+            .route(
+                "/logout",
+                post(handler_fn(|| async {
+                    Response::builder()
+                        .header(header::SET_COOKIE, "session=; Max-Age=0")
+                        .body(Body::empty())
+                })),
+            )
+            .route(
+                "/whoami",
+                get(handler_fn(
+                    |request: boluo_core::request::Request| async move {
+                        request
+                            .headers()
+                            .get(header::COOKIE)
+                            .map(|value| value.to_str().unwrap().to_owned())
+                            .unwrap_or_default()
+                            .into_response()
+                    },
+                )),
+            );
+        let client = TestClient::new(router);
+
+        client.post("/login").await.assert_status(StatusCode::OK);
+        assert_eq!(client.cookie("session").as_deref(), Some("abc"));
+        assert_eq!(client.get("/whoami").await.text().await, "session=abc");
+
+        let response = client.head("/whoami").await;
+        response.assert_header(header::CONTENT_LENGTH, "11");
+        assert!(response.bytes().await.is_empty());
+
+        client.post("/logout").await;
+        assert_eq!(client.cookie("session"), None);
+        assert_eq!(client.get("/whoami").await.text().await, "");
+    }
+
+    #[tokio::test]
+    async fn sse_events() {
+        let router = Router::new().route(
+            "/events",
+            get(handler_fn(|| async {
+                Response::builder()
+                    .header(header::CONTENT_TYPE, "text/event-stream")
+                    .body(Body::from(
+                        ": hello\n\nevent: a\ndata: 1\ndata: 2\n\r\nid: 3\nretry: 10\ndata: {\"b\":true}\n\n",
+                    ))
+            })),
+        );
+        let client = TestClient::new(router);
+
+        let mut events = client.get("/events").await.sse();
+        assert_eq!(events.recv().await.unwrap().comment(), Some("hello"));
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.event(), Some("a"));
+        assert_eq!(event.data(), Some("1\n2"));
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.id(), Some("3"));
+        assert_eq!(event.retry(), Some(std::time::Duration::from_millis(10)));
+        assert_eq!(
+            event.json::<serde_json::Value>(),
+            serde_json::json!({ "b": true })
+        );
+        assert!(events.recv().await.is_none());
+    }
+
+    #[cfg(feature = "ws")]
+    #[tokio::test]
+    async fn websocket_upgrade() {
+        use crate::ws::{Message, WebSocket, WebSocketUpgrade};
+
+        let router = Router::new().route(
+            "/ws",
+            get(handler_fn(|upgrade: WebSocketUpgrade| async move {
+                upgrade.on_upgrade(|mut socket: WebSocket| async move {
+                    while let Some(Ok(message)) = socket.recv().await {
+                        socket.send(message).await.ok();
+                    }
+                })
+            })),
+        );
+        let client = TestClient::new(router);
+
+        let response = client.websocket("/ws").await;
+        response.assert_status(StatusCode::SWITCHING_PROTOCOLS);
+        let mut socket = response.into_websocket().await;
+        socket.send(Message::text("ping")).await.unwrap();
+        assert_eq!(socket.recv().await.unwrap().unwrap(), Message::text("ping"));
+
+        let response = client.get("/ws").await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+    }
+}
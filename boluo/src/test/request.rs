@@ -0,0 +1,121 @@
+use std::future::IntoFuture;
+use std::pin::Pin;
+
+use boluo_core::BoxError;
+use boluo_core::body::Body;
+use boluo_core::http::header::{self, HeaderName, HeaderValue};
+use boluo_core::http::{Method, Uri};
+use boluo_core::request::RequestBuilder;
+
+use super::{TestClient, TestResponse};
+
+/// 测试请求构建器。
+///
+/// 调用 [`TestRequest::send`] 或直接 `.await` 发送请求。
+#[must_use = "requests do nothing unless you `.await` or call `send`"]
+pub struct TestRequest {
+    client: TestClient,
+    builder: RequestBuilder,
+    body: Result<Body, BoxError>,
+}
+
+impl TestRequest {
+    pub(super) fn new<T>(client: TestClient, method: Method, uri: T) -> Self
+    where
+        Uri: TryFrom<T>,
+        <Uri as TryFrom<T>>::Error: Into<boluo_core::http::Error>,
+    {
+        Self {
+            client,
+            builder: RequestBuilder::new().method(method).uri(uri),
+            body: Ok(Body::empty()),
+        }
+    }
+
+    /// 添加请求标头。
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<boluo_core::http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<boluo_core::http::Error>,
+    {
+        self.builder = self.builder.header(key, value);
+        self
+    }
+
+    /// 添加请求扩展。
+    pub fn extension<T>(mut self, extension: T) -> Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.builder = self.builder.extension(extension);
+        self
+    }
+
+    /// 设置请求主体。
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = Ok(body.into());
+        self
+    }
+
+    /// 将值序列化为 JSON 作为请求主体，并设置 `Content-Type` 标头。
+    pub fn json<T>(mut self, value: &T) -> Self
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        self.body = serde_json::to_vec(value)
+            .map(Body::from)
+            .map_err(From::from);
+        self.header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+    }
+
+    /// 将值序列化为表单作为请求主体，并设置 `Content-Type` 标头。
+    pub fn form<T>(mut self, value: &T) -> Self
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        self.body = serde_urlencoded::to_string(value)
+            .map(Body::from)
+            .map_err(From::from);
+        self.header(
+            header::CONTENT_TYPE,
+            mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+        )
+    }
+
+    /// 发送请求。
+    ///
+    /// # 恐慌
+    ///
+    /// 当请求构建失败时会发生恐慌。
+    pub async fn send(self) -> TestResponse {
+        let body = self
+            .body
+            .unwrap_or_else(|e| panic!("failed to build request body: {e}"));
+        let request = self
+            .builder
+            .body(body)
+            .unwrap_or_else(|e| panic!("failed to build request: {e}"));
+        self.client.send(request).await
+    }
+}
+
+impl IntoFuture for TestRequest {
+    type Output = TestResponse;
+    type IntoFuture = Pin<Box<dyn Future<Output = TestResponse> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+impl std::fmt::Debug for TestRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestRequest")
+            .field("method", &self.builder.method_ref())
+            .field("uri", &self.builder.uri_ref())
+            .field("headers", &self.builder.headers_ref())
+            .finish()
+    }
+}
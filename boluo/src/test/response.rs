@@ -0,0 +1,171 @@
+use boluo_core::BoxError;
+use boluo_core::body::Bytes;
+use boluo_core::http::header::{AsHeaderName, HeaderMap};
+use boluo_core::http::{StatusCode, Version};
+use boluo_core::response::Response;
+use boluo_core::upgrade::Upgraded;
+
+use super::SseStream;
+
+/// 测试请求的响应。
+///
+/// 服务返回错误时，响应由 [`ErrorResponder`] 生成，原始错误可以通过 [`TestResponse::error`] 获取。
+///
+/// [`ErrorResponder`]: crate::response::ErrorResponder
+pub struct TestResponse {
+    response: Response,
+    error: Option<BoxError>,
+    upgraded: Option<Upgraded>,
+}
+
+impl TestResponse {
+    pub(super) fn new(
+        response: Response,
+        error: Option<BoxError>,
+        upgraded: Option<Upgraded>,
+    ) -> Self {
+        Self {
+            response,
+            error,
+            upgraded,
+        }
+    }
+
+    /// 获取响应的状态码。
+    pub fn status(&self) -> StatusCode {
+        self.response.status()
+    }
+
+    /// 获取响应的版本。
+    pub fn version(&self) -> Version {
+        self.response.version()
+    }
+
+    /// 获取响应的标头集合。
+    pub fn headers(&self) -> &HeaderMap {
+        self.response.headers()
+    }
+
+    /// 获取响应标头的值，标头不存在或值不是有效的字符串时返回 `None`。
+    pub fn header<K>(&self, key: K) -> Option<&str>
+    where
+        K: AsHeaderName,
+    {
+        self.headers()
+            .get(key)
+            .and_then(|value| value.to_str().ok())
+    }
+
+    /// 获取服务返回的错误。
+    pub fn error(&self) -> Option<&BoxError> {
+        self.error.as_ref()
+    }
+
+    /// 断言响应的状态码。
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(self.status(), status, "unexpected response status");
+        self
+    }
+
+    /// 断言响应标头的值。
+    #[track_caller]
+    pub fn assert_header<K>(&self, key: K, value: &str) -> &Self
+    where
+        K: AsHeaderName + std::fmt::Display + Clone,
+    {
+        assert_eq!(
+            self.header(key.clone()),
+            Some(value),
+            "unexpected value of header `{key}`"
+        );
+        self
+    }
+
+    /// 消耗测试响应，得到内部的 [`Response`]。
+    pub fn into_inner(self) -> Response {
+        self.response
+    }
+
+    /// 读取响应主体。
+    ///
+    /// # 恐慌
+    ///
+    /// 当读取响应主体失败时会发生恐慌。
+    pub async fn bytes(self) -> Bytes {
+        self.response
+            .into_body()
+            .to_bytes()
+            .await
+            .unwrap_or_else(|e| panic!("failed to read response body: {e}"))
+    }
+
+    /// 读取响应主体并转换为字符串。
+    ///
+    /// # 恐慌
+    ///
+    /// 当读取响应主体失败或主体不是有效的 UTF-8 时会发生恐慌。
+    pub async fn text(self) -> String {
+        String::from_utf8(self.bytes().await.into())
+            .unwrap_or_else(|e| panic!("response body is not valid utf-8: {e}"))
+    }
+
+    /// 读取响应主体并反序列化为 JSON。
+    ///
+    /// # 恐慌
+    ///
+    /// 当读取响应主体失败或主体不是有效的 JSON 时会发生恐慌。
+    pub async fn json<T>(self) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::from_slice(&self.bytes().await)
+            .unwrap_or_else(|e| panic!("failed to parse response body: {e}"))
+    }
+
+    /// 将响应主体作为服务器发送事件流读取。
+    pub fn sse(self) -> SseStream {
+        SseStream::new(self.response.into_body().into_data_stream())
+    }
+
+    /// 获取协议升级后客户端一侧的连接。
+    ///
+    /// # 恐慌
+    ///
+    /// 当响应的状态码不是 `101 Switching Protocols` 时会发生恐慌。
+    #[track_caller]
+    pub fn into_upgraded(self) -> Upgraded {
+        match self.upgraded {
+            Some(upgraded) => upgraded,
+            None => panic!(
+                "connection was not upgraded, response status is {}",
+                self.response.status()
+            ),
+        }
+    }
+
+    /// 获取协议升级后客户端一侧的 WebSocket 连接。
+    ///
+    /// # 恐慌
+    ///
+    /// 当响应的状态码不是 `101 Switching Protocols` 时会发生恐慌。
+    #[cfg(feature = "ws")]
+    pub async fn into_websocket(self) -> crate::ws::WebSocket {
+        let upgraded = self.into_upgraded();
+        crate::ws::WebSocket::from_raw_socket(
+            upgraded,
+            tokio_tungstenite::tungstenite::protocol::Role::Client,
+            None,
+        )
+        .await
+    }
+}
+
+impl std::fmt::Debug for TestResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestResponse")
+            .field("response", &self.response)
+            .field("error", &self.error)
+            .finish()
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use boluo_core::body::BodyDataStream;
+use futures_util::StreamExt;
+
+/// 从响应主体中解析出的服务器发送事件。
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    event: Option<String>,
+    data: Option<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+    comment: Option<String>,
+}
+
+impl SseEvent {
+    /// 事件的类型。
+    pub fn event(&self) -> Option<&str> {
+        self.event.as_deref()
+    }
+
+    /// 事件的数据，多行数据使用 `\n` 连接。
+    pub fn data(&self) -> Option<&str> {
+        self.data.as_deref()
+    }
+
+    /// 将事件的数据反序列化为 JSON。
+    ///
+    /// # 恐慌
+    ///
+    /// 当事件没有数据或数据不是有效的 JSON 时会发生恐慌。
+    #[track_caller]
+    pub fn json<T>(&self) -> T
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let data = self.data().expect("event has no data");
+        serde_json::from_str(data).unwrap_or_else(|e| panic!("failed to parse event data: {e}"))
+    }
+
+    /// 事件的标识符。
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// 事件的重连时间。
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// 事件的注释，多行注释使用 `\n` 连接。
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    fn is_empty(&self) -> bool {
+        self == &SseEvent::default()
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "" => append_line(&mut self.comment, value),
+            "event" => self.event = Some(value.to_owned()),
+            "data" => append_line(&mut self.data, value),
+            "id" => self.id = Some(value.to_owned()),
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn append_line(field: &mut Option<String>, value: &str) {
+    match field {
+        Some(field) => {
+            field.push('\n');
+            field.push_str(value);
+        }
+        None => *field = Some(value.to_owned()),
+    }
+}
+
+/// 服务器发送事件流。
+pub struct SseStream {
+    stream: BodyDataStream,
+    buf: Vec<u8>,
+    eof: bool,
+}
+
+impl SseStream {
+    pub(super) fn new(stream: BodyDataStream) -> Self {
+        Self {
+            stream,
+            buf: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// 接收下一个事件，事件流结束时返回 `None`。
+    ///
+    /// # 恐慌
+    ///
+    /// 当读取响应主体失败或事件不是有效的 UTF-8 时会发生恐慌。
+    pub async fn recv(&mut self) -> Option<SseEvent> {
+        let mut event = SseEvent::default();
+        loop {
+            while let Some(line) = self.next_line() {
+                if !line.is_empty() {
+                    event.parse_line(&line);
+                } else if !event.is_empty() {
+                    return Some(event);
+                }
+            }
+            if self.eof {
+                return None;
+            }
+            match self.stream.next().await {
+                Some(Ok(data)) => self.buf.extend_from_slice(&data),
+                Some(Err(e)) => panic!("failed to read response body: {e}"),
+                None => self.eof = true,
+            }
+        }
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        let pos = self.buf.iter().position(|b| *b == b'\n')?;
+        let mut line = self.buf.drain(..=pos).collect::<Vec<_>>();
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(String::from_utf8(line).expect("event is not valid utf-8"))
+    }
+}
+
+impl std::fmt::Debug for SseStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SseStream").finish()
+    }
+}
@@ -0,0 +1,31 @@
+use boluo_core::BoxError;
+use boluo_core::request::Request;
+use boluo_core::upgrade::{OnUpgrade, Upgraded};
+use tokio::sync::oneshot;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+const PIPE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 使用内存管道模拟的协议升级。
+pub(super) struct UpgradePipe {
+    tx: oneshot::Sender<Upgraded>,
+}
+
+impl UpgradePipe {
+    /// 为请求添加 [`OnUpgrade`] 扩展。
+    pub(super) fn attach(request: &mut Request) -> Self {
+        let (tx, rx) = oneshot::channel();
+        request.extensions_mut().insert(OnUpgrade::new(async move {
+            rx.await
+                .map_err(|_| BoxError::from("connection was not upgraded"))
+        }));
+        Self { tx }
+    }
+
+    /// 完成协议升级，返回客户端一侧的连接。
+    pub(super) fn upgrade(self) -> Upgraded {
+        let (client, server) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+        self.tx.send(Upgraded::new(server.compat())).ok();
+        Upgraded::new(client.compat())
+    }
+}
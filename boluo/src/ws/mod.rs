@@ -14,7 +14,7 @@ use boluo_core::extract::FromRequest;
 use boluo_core::http::StatusCode;
 use boluo_core::http::header::{self, HeaderValue};
 use boluo_core::request::Request;
-use boluo_core::response::{IntoResponse, Response};
+use boluo_core::response::{ErrorResponse, IntoResponse, Response};
 use boluo_core::upgrade::{OnUpgrade, OnUpgradeError, Upgraded};
 use futures_util::{FutureExt, Sink, SinkExt, Stream, StreamExt, ready};
 use tokio_tungstenite::WebSocketStream;
@@ -235,7 +235,7 @@ pub struct WebSocket {
 }
 
 impl WebSocket {
-    async fn from_raw_socket(
+    pub(crate) async fn from_raw_socket(
         upgraded: Upgraded,
         role: protocol::Role,
         config: Option<WebSocketConfig>,
@@ -337,3 +337,38 @@ impl std::fmt::Display for WebSocketUpgradeError {
 }
 
 impl std::error::Error for WebSocketUpgradeError {}
+
+impl ErrorResponse for WebSocketUpgradeError {
+    fn status(&self) -> StatusCode {
+        match self {
+            WebSocketUpgradeError::InvalidUpgradeHeader => StatusCode::UPGRADE_REQUIRED,
+            // 请求本身有效，但服务器没有提供升级连接的能力。
+            WebSocketUpgradeError::ConnectionNotUpgradable => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use boluo_core::http::StatusCode;
+    use boluo_core::response::ErrorResponse;
+
+    use super::WebSocketUpgradeError;
+
+    #[test]
+    fn upgrade_error_status() {
+        assert_eq!(
+            WebSocketUpgradeError::InvalidUpgradeHeader.status(),
+            StatusCode::UPGRADE_REQUIRED
+        );
+        assert_eq!(
+            WebSocketUpgradeError::MissingSecWebSocketKeyHeader.status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            WebSocketUpgradeError::ConnectionNotUpgradable.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use boluo::BoxError;
+use boluo::extract::Form;
+use boluo::response::IntoResponse;
+use boluo::route::Router;
+use boluo::server::Server;
+use boluo::service::ServiceExt;
+use boluo::session::{FileStore, Session, SessionManager};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() {
+    let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+
+    // 会话保存在 `sessions` 目录中，服务重启后仍然有效
+    let sessions = SessionManager::new(FileStore::new("sessions")).secure(false);
+
+    let app = Router::new()
+        .mount(login)
+        .mount(me)
+        .mount(logout)
+        .with(sessions);
+
+    Server::new(listener).run(app).await.unwrap();
+}
+
+#[derive(Deserialize)]
+struct Login {
+    username: String,
+}
+
+#[boluo::route("/login", method = "POST")]
+async fn login(session: Session, Form(login): Form<Login>) -> Result<impl IntoResponse, BoxError> {
+    session.cycle_id(); // 登录后轮换会话标识符，防止会话固定攻击
+    session.insert("username", login.username)?;
+    Ok("ok")
+}
+
+#[boluo::route("/me", method = "GET")]
+async fn me(session: Session) -> Result<impl IntoResponse, BoxError> {
+    Ok(match session.get::<String>("username")? {
+        Some(username) => format!("hello, {username}"),
+        None => "anonymous".to_owned(),
+    })
+}
+
+#[boluo::route("/logout", method = "POST")]
+async fn logout(session: Session) -> impl IntoResponse {
+    session.purge(); // 删除会话并使 Cookie 过期
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use boluo::data::Extension;
+use boluo::listener::{CertResolver, ConnectInfo, TlsConfig, TlsConnectInfo, TlsListener};
+use boluo::response::IntoResponse;
+use boluo::route::Router;
+use boluo::server::Server;
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() {
+    let cert = "tls/self_signed_certs/cert.pem";
+    let key = "tls/self_signed_certs/key.pem";
+
+    // 证书文件变化后自动重载，新的连接会使用新证书
+    let resolver = CertResolver::from_pem_file(cert, key).await.unwrap();
+    resolver.watch_pem_file(cert, key, Duration::from_secs(10));
+
+    let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+    let listener = TlsListener::new(listener, TlsConfig::new(resolver));
+
+    let app = Router::new().mount(hello);
+
+    Server::new(listener).run(app).await.unwrap();
+}
+
+#[boluo::route("/", method = "GET")]
+async fn hello(Extension(info): Extension<TlsConnectInfo<ConnectInfo>>) -> impl IntoResponse {
+    format!(
+        "hello, {} (sni: {:?}, alpn: {:?})",
+        info.addr().remote,
+        info.server_name(),
+        info.alpn_protocol().map(String::from_utf8_lossy),
+    )
+}
@@ -0,0 +1,33 @@
+use boluo::extract::Path;
+use boluo::middleware::Trace;
+use boluo::response::IntoResponse;
+use boluo::route::Router;
+use boluo::server::Server;
+use tokio::net::TcpListener;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    let listener = TcpListener::bind("127.0.0.1:3000").await.unwrap();
+
+    // 为路由器内的服务分别应用中间件，使日志可以记录匹配的路由路径。
+    let app = Router::new().mount(hello).mount(user).with_for_each(Trace::new());
+
+    Server::new(listener).run(app).await.unwrap();
+}
+
+#[boluo::route("/", method = "GET")]
+async fn hello() -> impl IntoResponse {
+    "Hello, World!"
+}
+
+#[boluo::route("/users/{id}", method = "GET")]
+async fn user(Path(id): Path<u32>) -> impl IntoResponse {
+    format!("user {id}")
+}